@@ -0,0 +1,84 @@
+//! Latency of bulk `get_account` reads with a client built per read, as
+//! `get_account` used to do, against one `CnctdSolana` reusing its pooled
+//! connections.
+//!
+//! Runs against a local JSON-RPC stub by default, which isolates TCP setup
+//! cost. Set `BENCH_RPC_URL` to measure a real provider, where the TLS
+//! handshake makes the difference much larger:
+//!
+//!     BENCH_RPC_URL=https://api.devnet.solana.com cargo bench --bench bulk_reads
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use cnctd_solana::rpc::{client::CnctdSolana, RpcMode, RpcPool};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use common::StubRpc;
+
+const READS: usize = 200;
+
+fn account_info() -> Value {
+    json!({
+        "context": { "slot": 1 },
+        "value": {
+            "data": ["", "base64"],
+            "executable": false,
+            "lamports": 1_000_000,
+            "owner": "11111111111111111111111111111111",
+            "rentEpoch": 0,
+            "space": 0
+        }
+    })
+}
+
+fn pool(url: &str) -> RpcPool {
+    RpcPool::new(vec![url.to_string()], RpcMode::Failover).with_rate_limit(url, None)
+}
+
+fn report(name: &str, elapsed: Duration, connections: Option<usize>) {
+    let per_read = elapsed / READS as u32;
+    match connections {
+        Some(connections) => println!("{:<24} {:>10.2?} total {:>10.2?}/read {:>5} connections", name, elapsed, per_read, connections),
+        None => println!("{:<24} {:>10.2?} total {:>10.2?}/read", name, elapsed, per_read),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let remote = std::env::var("BENCH_RPC_URL").ok();
+    // Every request is answered with an account
+    let stub = match &remote {
+        Some(_) => None,
+        None => Some(StubRpc::start(|_, _| account_info()).await),
+    };
+    let url = remote.clone().unwrap_or_else(|| stub.as_ref().unwrap().url.clone());
+    let pubkey = Pubkey::from_str("11111111111111111111111111111111").unwrap();
+    let counted = || stub.as_ref().map(StubRpc::take_connections);
+
+    println!("{} sequential get_account reads against {}", READS, url);
+
+    // Warm up, so neither case pays for first-time DNS resolution
+    CnctdSolana::with_pool(pool(&url)).unwrap().get_account(pubkey).await.expect("warm-up read");
+    counted();
+
+    let start = Instant::now();
+    for _ in 0..READS {
+        let client = CnctdSolana::with_pool(pool(&url)).unwrap();
+        client.get_account(pubkey).await.expect("read");
+    }
+    report("client per read", start.elapsed(), counted());
+
+    let client = CnctdSolana::with_pool(pool(&url)).unwrap();
+    let start = Instant::now();
+    for _ in 0..READS {
+        client.get_account(pubkey).await.expect("read");
+    }
+    report("shared pooled client", start.elapsed(), counted());
+}
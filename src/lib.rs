@@ -1,6 +1,8 @@
+#[allow(ambiguous_glob_reexports)]
 pub use solana_sdk::*;
 pub use solana_client::*;
 pub use solana_account_decoder_client_types::*;
+#[allow(ambiguous_glob_reexports)]
 pub use solana_address::*;
 pub use solana_compute_budget_interface::*;
 pub use solana_system_interface::instruction as system_instruction;
@@ -0,0 +1,315 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{atomic::{AtomicU64, Ordering}, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use tokio::{sync::watch, task::JoinHandle};
+
+/// How long a cached account is served for unless overridden per account
+pub const DEFAULT_ACCOUNT_TTL: Duration = Duration::from_secs(5);
+
+pub const DEFAULT_MAX_CACHED_ACCOUNTS: usize = 10_000;
+
+/// Providers commonly cap the subscriptions on one connection near here
+pub const DEFAULT_MAX_WATCHED_ACCOUNTS: usize = 1_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct AccountCacheConfig {
+    pub default_ttl: Duration,
+    /// Subscribe to each cached account and apply its updates as they arrive
+    pub watch: bool,
+    /// Entries kept before the one closest to expiring is evicted
+    pub max_entries: usize,
+    /// Accounts subscribed to at once. Accounts cached beyond it are only
+    /// refreshed when their entry expires.
+    pub max_watched: usize,
+}
+
+impl Default for AccountCacheConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl: DEFAULT_ACCOUNT_TTL,
+            watch: true,
+            max_entries: DEFAULT_MAX_CACHED_ACCOUNTS,
+            max_watched: DEFAULT_MAX_WATCHED_ACCOUNTS,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct AccountCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries refreshed by subscription notifications
+    pub updates: u64,
+    pub entries: usize,
+    pub watched: usize,
+}
+
+pub enum CacheLookup {
+    /// A fresh entry; `None` when the account was cached as missing
+    Hit(Option<Account>),
+    /// Nothing fresh is cached. A fetch should ask for at least
+    /// `min_context_slot` so it never returns older state than was served.
+    Miss { min_context_slot: Option<u64> },
+}
+
+/// A notification from the subscription shared by watched accounts
+pub(crate) enum WatchedAccountUpdate {
+    Changed { pubkey: Pubkey, slot: u64, account: Option<Account> },
+    /// Notifications may have been missed, after a reconnect, or one could
+    /// not be decoded
+    Stale(Pubkey),
+}
+
+struct CacheEntry {
+    account: Option<Account>,
+    slot: u64,
+    expires_at: Instant,
+}
+
+/// Read-through cache for `CnctdSolana::get_account`.
+///
+/// Entries remember the slot they were read at, even once expired, so an
+/// entry is never replaced by older state from a lagging node.
+///
+/// Watched accounts share one subscription connection, which follows the
+/// watched set as accounts are added, invalidated and evicted.
+pub struct AccountCache {
+    config: AccountCacheConfig,
+    entries: Mutex<HashMap<Pubkey, CacheEntry>>,
+    ttls: Mutex<HashMap<Pubkey, Duration>>,
+    watched: watch::Sender<HashSet<Pubkey>>,
+    watcher: Mutex<Option<JoinHandle<()>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    updates: AtomicU64,
+}
+
+impl AccountCache {
+    pub fn new(config: AccountCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+            ttls: Mutex::new(HashMap::new()),
+            watched: watch::Sender::new(HashSet::new()),
+            watcher: Mutex::new(None),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            updates: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> AccountCacheConfig {
+        self.config
+    }
+
+    pub fn set_ttl(&self, pubkey: Pubkey, ttl: Duration) {
+        self.ttls.lock().unwrap().insert(pubkey, ttl);
+    }
+
+    pub fn ttl(&self, pubkey: &Pubkey) -> Duration {
+        self.ttls.lock().unwrap().get(pubkey).copied().unwrap_or(self.config.default_ttl)
+    }
+
+    /// Looks up `pubkey`, counting a hit or a miss
+    pub fn lookup(&self, pubkey: &Pubkey) -> CacheLookup {
+        let entries = self.entries.lock().unwrap();
+
+        match entries.get(pubkey) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                CacheLookup::Hit(entry.account.clone())
+            }
+            entry => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                CacheLookup::Miss { min_context_slot: entry.map(|entry| entry.slot) }
+            }
+        }
+    }
+
+    /// Caches `account` as read at `slot` and returns the newest known state,
+    /// which is the existing entry if it was read at a later slot. A new
+    /// entry in a full cache evicts expired entries, or failing that the one
+    /// closest to expiring.
+    pub fn insert(&self, pubkey: Pubkey, account: Option<Account>, slot: u64) -> Option<Account> {
+        let now = Instant::now();
+        let expires_at = now + self.ttl(&pubkey);
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(&pubkey) {
+            Some(entry) if entry.slot > slot => return entry.account.clone(),
+            Some(_) => {}
+            None if entries.len() >= self.config.max_entries => {
+                let mut evicted: Vec<Pubkey> = entries.iter()
+                    .filter(|(_, entry)| entry.expires_at <= now)
+                    .map(|(pubkey, _)| *pubkey)
+                    .collect();
+                if evicted.is_empty() {
+                    evicted.extend(entries.iter().min_by_key(|(_, entry)| entry.expires_at).map(|(pubkey, _)| *pubkey));
+                }
+
+                for pubkey in &evicted {
+                    entries.remove(pubkey);
+                }
+                self.unwatch(&evicted);
+            }
+            None => {}
+        }
+
+        entries.insert(pubkey, CacheEntry { account: account.clone(), slot, expires_at });
+        account
+    }
+
+    /// Applies a subscription notification for a watched account
+    pub fn update(&self, pubkey: Pubkey, account: Option<Account>, slot: u64) {
+        // A notification sent before the account stopped being watched
+        if !self.watched.borrow().contains(&pubkey) {
+            return;
+        }
+
+        self.updates.fetch_add(1, Ordering::Relaxed);
+        self.insert(pubkey, account, slot);
+    }
+
+    /// Forces the next read of `pubkey` to fetch, keeping its slot guard,
+    /// and stops watching it until then
+    pub fn invalidate(&self, pubkey: &Pubkey) {
+        self.expire(pubkey);
+        self.unwatch(std::slice::from_ref(pubkey));
+    }
+
+    /// Drops every entry and closes the subscription connection
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        self.watched.send_modify(HashSet::clear);
+
+        if let Some(watcher) = self.watcher.lock().unwrap().take() {
+            watcher.abort();
+        }
+    }
+
+    /// Forces the next read of `pubkey` to fetch, for an account whose
+    /// notifications may have been missed
+    pub(crate) fn expire(&self, pubkey: &Pubkey) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(pubkey) {
+            entry.expires_at = Instant::now();
+        }
+    }
+
+    /// Adds `pubkey` to the watched set, unless `max_watched` accounts are
+    /// already watched. The first account starts the task from `spawn`,
+    /// which subscribes to the set it is given and follows its changes.
+    pub(crate) fn watch_with(&self, pubkey: Pubkey, spawn: impl FnOnce(watch::Receiver<HashSet<Pubkey>>) -> JoinHandle<()>) {
+        let mut watcher = self.watcher.lock().unwrap();
+
+        self.watched.send_if_modified(|watched| watched.len() < self.config.max_watched && watched.insert(pubkey));
+
+        if watcher.as_ref().is_none_or(JoinHandle::is_finished) {
+            *watcher = Some(spawn(self.watched.subscribe()));
+        }
+    }
+
+    fn unwatch(&self, pubkeys: &[Pubkey]) {
+        self.watched.send_if_modified(|watched| {
+            let watching = watched.len();
+            for pubkey in pubkeys {
+                watched.remove(pubkey);
+            }
+
+            watched.len() < watching
+        });
+    }
+
+    pub fn stats(&self) -> AccountCacheStats {
+        AccountCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            updates: self.updates.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+            watched: self.watched.borrow().len(),
+        }
+    }
+}
+
+impl Drop for AccountCache {
+    fn drop(&mut self) {
+        if let Some(watcher) = self.watcher.lock().unwrap().take() {
+            watcher.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(max_entries: usize, max_watched: usize) -> AccountCache {
+        AccountCache::new(AccountCacheConfig { max_entries, max_watched, ..AccountCacheConfig::default() })
+    }
+
+    fn account(lamports: u64) -> Option<Account> {
+        Some(Account { lamports, ..Account::default() })
+    }
+
+    #[test]
+    fn evicts_the_entry_closest_to_expiring_when_full() {
+        let cache = cache(2, 2);
+        let [first, second, third] = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        cache.set_ttl(first, Duration::from_secs(1));
+
+        cache.insert(first, account(1), 1);
+        cache.insert(second, account(2), 1);
+        cache.insert(third, account(3), 1);
+
+        assert_eq!(cache.stats().entries, 2);
+        assert!(matches!(cache.lookup(&first), CacheLookup::Miss { min_context_slot: None }));
+        assert!(matches!(cache.lookup(&third), CacheLookup::Hit(Some(_))));
+    }
+
+    #[tokio::test]
+    async fn shares_one_watcher_up_to_max_watched() {
+        let cache = cache(10, 2);
+        let mut spawned = 0;
+        for _ in 0..3 {
+            cache.watch_with(Pubkey::new_unique(), |_| {
+                spawned += 1;
+                tokio::spawn(std::future::pending())
+            });
+        }
+
+        assert_eq!(spawned, 1);
+        assert_eq!(cache.stats().watched, 2);
+    }
+
+    #[tokio::test]
+    async fn invalidate_and_clear_stop_watching() {
+        let cache = cache(10, 10);
+        let [first, second] = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut watched = None;
+        for pubkey in [first, second] {
+            cache.insert(pubkey, account(1), 1);
+            cache.watch_with(pubkey, |receiver| {
+                watched = Some(receiver);
+                tokio::spawn(std::future::pending())
+            });
+        }
+        let watched = watched.unwrap();
+
+        cache.invalidate(&first);
+        assert_eq!(*watched.borrow(), HashSet::from([second]));
+
+        // Late notifications for an unwatched account are dropped
+        cache.update(first, account(5), 2);
+        assert!(matches!(cache.lookup(&first), CacheLookup::Miss { min_context_slot: Some(1) }));
+
+        cache.clear();
+        assert!(watched.borrow().is_empty());
+        assert!(cache.watcher.lock().unwrap().is_none());
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
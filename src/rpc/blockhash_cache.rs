@@ -0,0 +1,180 @@
+use std::{sync::{Arc, RwLock}, time::{Duration, Instant}};
+
+use serde::Serialize;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use tokio::{sync::Mutex, task::JoinHandle};
+
+use crate::rpc::{error::Result, RpcPool};
+
+/// How often the background task refreshes the cache unless overridden
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Cached values older than this are ignored, e.g. while every endpoint is
+/// failing, so callers fall back to fetching and see the real error
+pub const MAX_CACHED_AGE: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CachedChainState {
+    pub blockhash: Hash,
+    pub last_valid_block_height: u64,
+    pub slot: u64,
+    #[serde(skip)]
+    pub fetched_at: Instant,
+}
+
+struct Shared {
+    pool: RpcPool,
+    commitment: CommitmentConfig,
+    state: RwLock<Option<CachedChainState>>,
+    /// Held while fetching, so concurrent refreshes share one fetch
+    refreshing: Mutex<()>,
+}
+
+impl Shared {
+    fn fresh(&self, max_age: Duration) -> Option<CachedChainState> {
+        self.state
+            .read()
+            .unwrap()
+            .filter(|state| state.fetched_at.elapsed() <= max_age)
+    }
+
+    /// Fetches unless the state is younger than `max_age`, checked again
+    /// once any fetch already under way has finished
+    async fn refresh(&self, max_age: Duration) -> Result<CachedChainState> {
+        let _refreshing = self.refreshing.lock().await;
+        if let Some(state) = self.fresh(max_age) {
+            return Ok(state);
+        }
+
+        let commitment = self.commitment;
+        let (blockhash, slot) = tokio::join!(
+            self.pool.call("get_latest_blockhash", |transport| async move {
+                transport.get_latest_blockhash_with_commitment(commitment).await
+            }),
+            self.pool.call("get_slot", |transport| async move {
+                transport.get_slot_with_commitment(commitment).await
+            }),
+        );
+        let ((blockhash, last_valid_block_height), slot) = (blockhash?, slot?);
+
+        let state = CachedChainState {
+            blockhash,
+            last_valid_block_height,
+            slot,
+            fetched_at: Instant::now(),
+        };
+        *self.state.write().unwrap() = Some(state);
+
+        Ok(state)
+    }
+}
+
+/// Latest blockhash and slot, kept fresh by a background task that runs
+/// until the cache is dropped.
+pub struct BlockhashCache {
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl BlockhashCache {
+    /// Starts refreshing from `pool` every `interval`, at `commitment`. Must
+    /// be called from within a Tokio runtime.
+    pub fn spawn(pool: RpcPool, interval: Duration, commitment: CommitmentConfig) -> Self {
+        let shared = Arc::new(Shared {
+            pool,
+            commitment,
+            state: RwLock::new(None),
+            refreshing: Mutex::new(()),
+        });
+
+        let background = shared.clone();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+
+                // Skipped when a caller refreshed since the last tick
+                if let Err(e) = background.refresh(interval / 2).await {
+                    tracing::warn!(error = %e, "Failed to refresh blockhash cache");
+                }
+            }
+        });
+
+        Self { shared, task }
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.shared.commitment
+    }
+
+    /// The cached state, unless it is older than `MAX_CACHED_AGE`
+    pub fn get(&self) -> Option<CachedChainState> {
+        self.shared.fresh(MAX_CACHED_AGE)
+    }
+
+    /// The cached state, or a fresh one when it is missing or older than
+    /// `MAX_CACHED_AGE`. Callers arriving while a fetch is under way wait
+    /// for it rather than sending their own.
+    pub async fn get_or_refresh(&self) -> Result<CachedChainState> {
+        match self.get() {
+            Some(state) => Ok(state),
+            None => self.shared.refresh(MAX_CACHED_AGE).await,
+        }
+    }
+}
+
+impl Drop for BlockhashCache {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::join_all;
+
+    use super::*;
+    use crate::rpc::{health::HealthConfig, MemoryTransport, RpcEndpoint, RpcMode};
+
+    fn pool(memory: &Arc<MemoryTransport>) -> RpcPool {
+        RpcPool::from_endpoints(vec![RpcEndpoint::with_transport(memory.clone())], RpcMode::Failover)
+    }
+
+    fn calls(pool: &RpcPool) -> u64 {
+        let endpoint = &pool.endpoints()[0];
+        endpoint.health.snapshot(&endpoint.url, &endpoint.domain(), &HealthConfig::default()).successes
+    }
+
+    #[tokio::test]
+    async fn refreshes_in_the_background_before_going_stale() {
+        let memory = Arc::new(MemoryTransport::new());
+        let cache = BlockhashCache::spawn(pool(&memory), Duration::from_millis(20), CommitmentConfig::confirmed());
+
+        let first = cache.get_or_refresh().await.unwrap();
+        memory.advance_slot(5);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let refreshed = cache.get().unwrap();
+        assert_eq!(refreshed.slot, first.slot + 5);
+        assert_ne!(refreshed.blockhash, first.blockhash);
+        assert!(refreshed.fetched_at > first.fetched_at);
+    }
+
+    #[tokio::test]
+    async fn concurrent_callers_share_one_fetch() {
+        let memory = Arc::new(MemoryTransport::new());
+        let pool = pool(&memory);
+        // Only the first tick fires during the test
+        let cache = BlockhashCache::spawn(pool.clone(), Duration::from_secs(3600), CommitmentConfig::confirmed());
+
+        let states = join_all((0..10).map(|_| cache.get_or_refresh())).await;
+
+        assert!(states.iter().all(|state| state.as_ref().unwrap().slot == memory.slot()));
+        // One blockhash and one slot request, between the callers and the first tick
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(calls(&pool), 2);
+    }
+}
@@ -1,16 +1,27 @@
+use std::{collections::{HashMap, HashSet}, future::Future, path::{Path, PathBuf}, str::FromStr, sync::Arc, time::Duration};
+
 use base64::Engine;
+use futures::{future, stream::{self, SelectAll, StreamExt, TryStreamExt}};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use solana_account_decoder_client_types::UiAccountEncoding;
-use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig}, rpc_filter::{Memcmp, RpcFilterType}};
+use solana_account_decoder_client_types::{UiAccount, UiAccountEncoding};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSignatureSubscribeConfig, RpcSimulateTransactionConfig}, rpc_filter::{Memcmp, RpcFilterType}, rpc_response::RpcSignatureResult};
 use solana_commitment_config::CommitmentConfig;
 use solana_sdk::{
-    account::Account, hash::hashv, instruction::{AccountMeta, Instruction}, message::Message, pubkey::Pubkey, signature::{Keypair, Signature}, transaction::Transaction
+    account::Account, hash::{hashv, Hash}, instruction::{AccountMeta, Instruction}, message::Message, pubkey::Pubkey, signature::{Keypair, Signature}, transaction::Transaction
 };
 use borsh::{BorshSerialize, BorshDeserialize};
-use anyhow::{Result, anyhow};
+use tokio::sync::watch;
+
+use crate::{rpc::{account_cache::{AccountCache, AccountCacheConfig, AccountCacheStats, CacheLookup, WatchedAccountUpdate}, blockhash_cache::{BlockhashCache, CachedChainState}, confirmation::CONFIRMATION_POLL_INTERVAL, error::{CnctdSolanaError, Result}, rebroadcast::DEFAULT_REBROADCAST_INTERVAL, ComputeBudgetConfig, ConfirmationOutcome, LookupTableCache, RpcEndpoint, RpcMode, RpcPool, RpcTransport, SlotTracker, Subscription}, utils::FilterableAccount};
+
+/// Most accounts a single `getMultipleAccounts` request may ask for
+pub const MULTIPLE_ACCOUNTS_BATCH_SIZE: usize = 100;
 
-use crate::utils::FilterableAccount;
+/// How many `getMultipleAccounts` batches are in flight at once
+pub const MULTIPLE_ACCOUNTS_CONCURRENCY: usize = 8;
+
+type UnsubscribeFn = Box<dyn FnOnce() -> future::BoxFuture<'static, ()> + Send>;
 
 pub trait TransactionExt {
     fn to_base64_string(&self) -> Result<String>;
@@ -18,7 +29,8 @@ pub trait TransactionExt {
 
 impl TransactionExt for Transaction {
     fn to_base64_string(&self) -> Result<String> {
-        let serialized_tx = bincode::serialize(self)?;
+        let serialized_tx = bincode::serialize(self)
+            .map_err(|e| CnctdSolanaError::Serialization(e.to_string()))?;
         let base64_tx = base64::engine::general_purpose::STANDARD.encode(serialized_tx);
     
         Ok(base64_tx)
@@ -36,33 +48,159 @@ pub enum PdaState {
 pub struct CnctdSolana {
     pub rpc_url: String,
     pub signer_keypair: Option<Keypair>,
-    pub client: RpcClient,
+    pub pool: RpcPool,
+    /// Used by every read and send that is not given its own commitment
+    pub commitment: CommitmentConfig,
+    pub blockhash_cache: Option<BlockhashCache>,
+    pub account_cache: Option<Arc<AccountCache>>,
+    /// Newest slots seen, passed as `min_context_slot` so that a read
+    /// failing over to a lagging endpoint cannot go back in time
+    pub slot_tracker: Arc<SlotTracker>,
+    /// Decoded lookup tables, see `get_lookup_tables`
+    pub lookup_table_cache: LookupTableCache,
+    /// Added by the transaction builders, see `with_compute_budget`
+    pub compute_budget: Option<ComputeBudgetConfig>,
+    /// How often `rebroadcast_until_confirmed` resends
+    pub rebroadcast_interval: Duration,
 }
 
 impl CnctdSolana {
     pub fn new(rpc_url: &str) -> Result<Self> {
+        Self::with_pool(RpcPool::single(rpc_url))
+    }
+
+    pub fn with_pool(pool: RpcPool) -> Result<Self> {
+        let rpc_url = pool.primary()
+            .map(|endpoint| endpoint.url.clone())
+            .ok_or_else(|| CnctdSolanaError::Config("RPC pool has no endpoints".to_string()))?;
+
         Ok(Self {
-            rpc_url: rpc_url.to_string(),
+            rpc_url,
             signer_keypair: None,
-            client: RpcClient::new(rpc_url.to_string()),
+            pool,
+            commitment: CommitmentConfig::default(),
+            blockhash_cache: None,
+            account_cache: None,
+            slot_tracker: Arc::new(SlotTracker::new()),
+            lookup_table_cache: LookupTableCache::default(),
+            compute_budget: None,
+            rebroadcast_interval: DEFAULT_REBROADCAST_INTERVAL,
         })
     }
 
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Serves `get_account` (and `get_account_data`) from an in-memory
+    /// cache for reads at the instance commitment. With `config.watch`, cached accounts are kept current through
+    /// account subscriptions, which needs a Tokio runtime.
+    pub fn with_account_cache(mut self, config: AccountCacheConfig) -> Self {
+        self.account_cache = Some(Arc::new(AccountCache::new(config)));
+        self
+    }
+
+    pub fn account_cache_stats(&self) -> Option<AccountCacheStats> {
+        self.account_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Keeps the latest blockhash and slot refreshed in the background every
+    /// `interval`, so send paths skip the blockhash round trip. The cache
+    /// uses the instance commitment, so set that first. Must be called from
+    /// within a Tokio runtime.
+    pub fn with_blockhash_cache(mut self, interval: Duration) -> Self {
+        self.blockhash_cache = Some(BlockhashCache::spawn(self.pool.clone(), interval, self.commitment));
+        self
+    }
+
+    /// Runs against a single transport, e.g. a `MemoryTransport` in tests
+    pub fn with_transport(transport: Arc<dyn RpcTransport>) -> Result<Self> {
+        Self::with_pool(RpcPool::from_transports(vec![transport], RpcMode::Failover))
+    }
+
+    /// Runs against `rpc_url`, recording every request and response to the
+    /// fixture at `path`
+    pub fn recording(rpc_url: &str, path: impl Into<PathBuf>) -> Result<Self> {
+        Self::with_pool(RpcPool::from_endpoints(vec![RpcEndpoint::recording(rpc_url, path)], RpcMode::Failover))
+    }
+
+    /// Runs offline against the fixture at `path`, as written by `recording`
+    pub fn replaying(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_pool(RpcPool::from_endpoints(vec![RpcEndpoint::replaying(path)?], RpcMode::Failover))
+    }
+
+    /// Transport for the pool's best-ranked endpoint, for calls not covered by `CnctdSolana`
+    pub fn transport(&self) -> Result<Arc<dyn RpcTransport>> {
+        self.pool.best_endpoint()
+            .map(|endpoint| endpoint.transport.clone())
+            .ok_or_else(|| CnctdSolanaError::Config("RPC pool has no endpoints".to_string()))
+    }
+
+    /// `RpcClient` for the pool's best-ranked endpoint, in place of the
+    /// `client` field calls used to go through. `None` for transports that
+    /// are not backed by one, such as `MemoryTransport`.
+    #[deprecated(note = "use the `CnctdSolana` methods, which route through the pool, or `transport()`")]
+    pub fn client(&self) -> Option<&RpcClient> {
+        self.pool.best_endpoint()?.transport.rpc_client()
+    }
+
     pub fn get_initialize_discriminator() -> Vec<u8> {
         let sighash = hashv(&[b"global:initialize"]); // Mimicking Anchor-style sighash
         sighash.as_ref()[..8].to_vec() // First 8 bytes as the discriminator
     }
 
     pub async fn get_latest_blockhash(&self) -> Result<String> {
-        let client = &self.client;
-        let blockhash = client.get_latest_blockhash().await?;
+        self.get_latest_blockhash_with_commitment(self.commitment).await
+    }
+
+    pub async fn get_latest_blockhash_with_commitment(&self, commitment: CommitmentConfig) -> Result<String> {
+        let (blockhash, _last_valid_block_height) = self.latest_blockhash(commitment).await?;
 
         Ok(blockhash.to_string())
     }
 
+    /// Latest blockhash and its last valid block height, from the blockhash
+    /// cache when it is running at `commitment`
+    pub(crate) async fn latest_blockhash(&self, commitment: CommitmentConfig) -> Result<(Hash, u64)> {
+        if let Some(cached) = self.cached_chain_state(commitment).await {
+            let cached = cached?;
+            return Ok((cached.blockhash, cached.last_valid_block_height));
+        }
+
+        self.pool.call("get_latest_blockhash", |transport| async move {
+            transport.get_latest_blockhash_with_commitment(commitment).await
+        }).await
+    }
+
+    /// `None` without a blockhash cache at `commitment`; otherwise its
+    /// state, refreshed first if it went stale
+    async fn cached_chain_state(&self, commitment: CommitmentConfig) -> Option<Result<CachedChainState>> {
+        let cache = self.blockhash_cache.as_ref().filter(|cache| cache.commitment() == commitment)?;
+
+        Some(cache.get_or_refresh().await)
+    }
+
+    pub async fn get_slot(&self) -> Result<u64> {
+        self.get_slot_with_commitment(self.commitment).await
+    }
+
+    pub async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> Result<u64> {
+        let slot = match self.cached_chain_state(commitment).await {
+            Some(cached) => cached?.slot,
+            None => self.pool.call("get_slot", |transport| async move {
+                transport.get_slot_with_commitment(commitment).await
+            }).await?,
+        };
+        self.slot_tracker.observe(commitment, slot);
+
+        Ok(slot)
+    }
+
     pub async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
-        let client = &self.client;
-        let min_balance = client.get_minimum_balance_for_rent_exemption(data_len).await?;
+        let min_balance = self.pool.call("get_minimum_balance_for_rent_exemption", |transport| async move {
+            transport.get_minimum_balance_for_rent_exemption(data_len).await
+        }).await?;
 
         Ok(min_balance)
     }
@@ -75,18 +213,22 @@ impl CnctdSolana {
         accounts: Vec<AccountMeta>, // Now contains payer info
     ) -> Result<Transaction> {
         let mut data = Self::get_discriminator(instruction_name).to_vec();
-        data.extend(borsh::to_vec(&instruction_data)?);
+        data.extend(borsh::to_vec(&instruction_data).map_err(|e| CnctdSolanaError::Serialization(e.to_string()))?);
     
         // Find the first writable signer (payer)
         let payer_pubkey = accounts
             .iter()
             .find(|meta| meta.is_signer && meta.is_writable)
             .map(|meta| meta.pubkey)
-            .ok_or_else(|| anyhow!("No writable signer found to act as payer"))?;
+            .ok_or_else(|| CnctdSolanaError::InvalidInput("No writable signer found to act as payer".to_string()))?;
     
         let instruction = Instruction::new_with_bytes(program_id, &data, accounts);
+        let instructions = match &self.compute_budget {
+            Some(config) => self.apply_compute_budget(&payer_pubkey, &[instruction], &[], config).await?,
+            None => vec![instruction],
+        };
     
-        let message = Message::new(&[instruction], Some(&payer_pubkey));
+        let message = Message::new(&instructions, Some(&payer_pubkey));
         let transaction = Transaction::new_unsigned(message);
 
         Ok(transaction)
@@ -100,7 +242,7 @@ impl CnctdSolana {
         accounts: Vec<AccountMeta>,
     ) -> Result<Instruction> {
         let mut data = Self::get_discriminator(instruction_name).to_vec();
-        data.extend(borsh::to_vec(&instruction_data)?);
+        data.extend(borsh::to_vec(&instruction_data).map_err(|e| CnctdSolanaError::Serialization(e.to_string()))?);
     
         let instruction = Instruction::new_with_bytes(program_id, &data, accounts);
     
@@ -108,59 +250,304 @@ impl CnctdSolana {
     }
 
     pub async fn get_pda_pubkey(&self, program_pubkey: Pubkey, seed: &str) -> Result<Pubkey> {
-        let rpc_client = RpcClient::new(self.rpc_url.clone());
-    
+        self.get_pda_pubkey_with_commitment(program_pubkey, seed, self.commitment).await
+    }
+
+    pub async fn get_pda_pubkey_with_commitment(&self, program_pubkey: Pubkey, seed: &str, commitment: CommitmentConfig) -> Result<Pubkey> {
         let (pda, _bump) = Pubkey::find_program_address(&[seed.as_bytes()], &program_pubkey);
     
-        match rpc_client.get_account(&pda).await {
-            Ok(account) => {
-                if account.lamports > 0 {
-                    Ok(pda)
-                } else {
-                    Err(anyhow!("PDA account has no lamports"))
-                }
-            }
-            Err(e) => Err(anyhow!("Error fetching PDA account: {}", e)),
+        let account = self.get_account_with_commitment(pda, commitment).await?;
+
+        // An account without lamports is about to be garbage collected
+        if account.lamports > 0 {
+            Ok(pda)
+        } else {
+            Err(CnctdSolanaError::AccountNotFound(pda))
         }
     }
 
     pub async fn get_account(&self, pubkey: Pubkey) -> Result<Account> {
-        let rpc_client = RpcClient::new(self.rpc_url.clone());
+        self.get_account_with_commitment(pubkey, self.commitment).await
+    }
+
+    /// Reads at another commitment bypass the account cache, which only
+    /// holds state read at the instance commitment
+    pub async fn get_account_with_commitment(&self, pubkey: Pubkey, commitment: CommitmentConfig) -> Result<Account> {
+        let account_cache = self.account_cache.as_ref().filter(|_| commitment == self.commitment);
+
+        let min_context_slot = match account_cache.map(|cache| cache.lookup(&pubkey)) {
+            Some(CacheLookup::Hit(account)) => return account.ok_or(CnctdSolanaError::AccountNotFound(pubkey)),
+            Some(CacheLookup::Miss { min_context_slot }) => min_context_slot,
+            None => None,
+        };
+
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: None,
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, min_context_slot),
+            commitment: Some(commitment),
+        };
+        let response = self.pool.call("get_account", |transport| {
+            let config = config.clone();
+            async move { transport.get_account_with_config(&pubkey, config).await }
+        }).await?;
+        self.slot_tracker.observe(commitment, response.context.slot);
+
+        let account = match account_cache {
+            Some(cache) => {
+                if cache.config().watch {
+                    self.watch_cached_account(cache, pubkey);
+                }
+                cache.insert(pubkey, response.value, response.context.slot)
+            }
+            None => response.value,
+        };
     
-        match rpc_client.get_account(&pubkey).await {
-            Ok(account) => Ok(account),
-            Err(e) => Err(anyhow!("Error fetching account: {}", e)),
-        }
+        account.ok_or(CnctdSolanaError::AccountNotFound(pubkey))
+    }
+
+    /// Adds `pubkey` to the accounts whose notifications are applied to the
+    /// cache, over one connection shared by all of them, until the cache is
+    /// cleared or dropped
+    fn watch_cached_account(&self, cache: &Arc<AccountCache>, pubkey: Pubkey) {
+        let cache_ref = Arc::downgrade(cache);
+
+        cache.watch_with(pubkey, |watched| {
+            // Same commitment as the cached reads, so slots compare like for like
+            let mut updates = self.watched_accounts_subscription(watched, self.commitment);
+
+            tokio::spawn(async move {
+                while let Some(update) = updates.next().await {
+                    let Some(cache) = cache_ref.upgrade() else { break };
+                    match update {
+                        WatchedAccountUpdate::Changed { pubkey, slot, account } => cache.update(pubkey, account, slot),
+                        WatchedAccountUpdate::Stale(pubkey) => cache.expire(&pubkey),
+                    }
+                }
+            })
+        });
+    }
+
+    /// Notifications for every account in `watched`, subscribing and
+    /// unsubscribing on one connection as the set changes
+    fn watched_accounts_subscription(
+        &self,
+        watched: watch::Receiver<HashSet<Pubkey>>,
+        commitment: CommitmentConfig,
+    ) -> Subscription<WatchedAccountUpdate> {
+        let slot_tracker = self.slot_tracker.clone();
+
+        Subscription::spawn("account", self.pool.ws_urls(), move |client, sender, resubscribe| {
+            let mut watched = watched.clone();
+            let slot_tracker = slot_tracker.clone();
+
+            async move {
+                let config = RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    data_slice: None,
+                    min_context_slot: None,
+                    commitment: Some(commitment),
+                };
+                let mut updates = SelectAll::new();
+                let mut subscriptions: HashMap<Pubkey, (stream::AbortHandle, UnsubscribeFn)> = HashMap::new();
+                let mut resubscribe = resubscribe;
+
+                loop {
+                    let wanted = watched.borrow_and_update().clone();
+
+                    let unwatched: Vec<Pubkey> = subscriptions.keys().filter(|pubkey| !wanted.contains(*pubkey)).copied().collect();
+                    for pubkey in unwatched {
+                        if let Some((abort, unsubscribe)) = subscriptions.remove(&pubkey) {
+                            abort.abort();
+                            unsubscribe().await;
+                        }
+                    }
+
+                    for pubkey in wanted {
+                        if subscriptions.contains_key(&pubkey) {
+                            continue;
+                        }
+
+                        let (notifications, unsubscribe) = client.account_subscribe(&pubkey, Some(config.clone())).await?;
+                        let (notifications, abort) = stream::abortable(notifications);
+                        // Ends with `None`, telling a subscription the node closed
+                        // from one aborted above
+                        updates.push(notifications.map(Some).chain(stream::once(future::ready(None))).map(move |update| (pubkey, update)));
+                        subscriptions.insert(pubkey, (abort, unsubscribe));
+
+                        if resubscribe && sender.send(WatchedAccountUpdate::Stale(pubkey)).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    resubscribe = false;
+
+                    tokio::select! {
+                        changed = watched.changed() => if changed.is_err() {
+                            return Ok(());
+                        },
+                        Some((pubkey, update)) = updates.next() => match update {
+                            Some(update) => {
+                                slot_tracker.observe(commitment, update.context.slot);
+                                let update = match Self::decode_ui_account(&update.value) {
+                                    Ok(account) => WatchedAccountUpdate::Changed {
+                                        pubkey,
+                                        slot: update.context.slot,
+                                        account: Some(account).filter(|account| account.lamports > 0),
+                                    },
+                                    Err(_) => WatchedAccountUpdate::Stale(pubkey),
+                                };
+                                if sender.send(update).is_err() {
+                                    return Ok(());
+                                }
+                            }
+                            // The connection dropped
+                            None if subscriptions.contains_key(&pubkey) => return Ok(()),
+                            None => {}
+                        },
+                    }
+                }
+            }
+        })
     }
     
-    pub async fn get_account_data<T: BorshDeserialize>(&self, pubkey: Pubkey) -> Result<T> {
-        let rpc_client = RpcClient::new(self.rpc_url.clone());
-        
-        match rpc_client.get_account(&pubkey).await {
-            Ok(account) => {
-                // Check if account data has at least the discriminator (8 bytes)
-                if account.data.len() < 8 {
-                    return Err(anyhow!("Account data is too short to be a valid Anchor account"));
+    /// Fetches `pubkeys` in batches of `MULTIPLE_ACCOUNTS_BATCH_SIZE`, with up
+    /// to `MULTIPLE_ACCOUNTS_CONCURRENCY` batches in flight. The result is
+    /// aligned to `pubkeys`, with `None` for accounts that do not exist.
+    pub async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.get_multiple_accounts_with_commitment(pubkeys, self.commitment).await
+    }
+
+    pub async fn get_multiple_accounts_with_commitment(&self, pubkeys: &[Pubkey], commitment: CommitmentConfig) -> Result<Vec<Option<Account>>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: None,
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
+            commitment: Some(commitment),
+        };
+
+        // `buffered` yields batches in input order, keeping the result aligned
+        let batches: Vec<Vec<Option<Account>>> = stream::iter(pubkeys.chunks(MULTIPLE_ACCOUNTS_BATCH_SIZE))
+            .map(|batch| {
+                let config = config.clone();
+                async move {
+                    let response = self.pool.call("get_multiple_accounts", |transport| {
+                        let config = config.clone();
+                        async move { transport.get_multiple_accounts_with_config(batch, config).await }
+                    }).await?;
+                    self.slot_tracker.observe(commitment, response.context.slot);
+
+                    Ok::<_, CnctdSolanaError>(response.value)
                 }
-                
-                // Skip the 8-byte discriminator and use the rest of the data
-                let data_slice = &account.data[8..];
-                
-                // Try to deserialize from the entire remaining data
-                let data = T::deserialize(&mut &data_slice[..])
-                    .map_err(|e| anyhow!("Failed to deserialize account data: {}", e))?;
-                    
-                Ok(data)
-            },
-            Err(e) => Err(anyhow!("Error fetching account: {}", e)),
+            })
+            .buffered(MULTIPLE_ACCOUNTS_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(batches.into_iter().flatten().collect())
+    }
+
+    pub async fn get_account_data<T: BorshDeserialize>(&self, pubkey: Pubkey) -> Result<T> {
+        self.get_account_data_with_commitment(pubkey, self.commitment).await
+    }
+
+    pub async fn get_account_data_with_commitment<T: BorshDeserialize>(&self, pubkey: Pubkey, commitment: CommitmentConfig) -> Result<T> {
+        let account = self.get_account_with_commitment(pubkey, commitment).await?;
+
+        Self::decode_account_data(&account.data)
+    }
+
+    /// Decodes many Anchor accounts with `get_multiple_accounts`. Each entry
+    /// lines up with `pubkeys`: `None` when the account does not exist, or
+    /// the account's own decode error, so one bad account does not fail the
+    /// whole load.
+    pub async fn get_multiple_accounts_data<T: BorshDeserialize>(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Result<T>>>> {
+        self.get_multiple_accounts_data_with_commitment(pubkeys, self.commitment).await
+    }
+
+    pub async fn get_multiple_accounts_data_with_commitment<T: BorshDeserialize>(
+        &self,
+        pubkeys: &[Pubkey],
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<Option<Result<T>>>> {
+        let accounts = self.get_multiple_accounts_with_commitment(pubkeys, commitment).await?;
+
+        Ok(accounts
+            .into_iter()
+            .map(|account| account.map(|account| Self::decode_account_data(&account.data)))
+            .collect())
+    }
+
+    /// Decodes Anchor account data, skipping the 8-byte discriminator
+    pub fn decode_account_data<T: BorshDeserialize>(data: &[u8]) -> Result<T> {
+        // Check if account data has at least the discriminator (8 bytes)
+        if data.len() < 8 {
+            return Err(CnctdSolanaError::Deserialization("Account data is too short to be a valid Anchor account".to_string()));
         }
+        
+        // Skip the 8-byte discriminator and use the rest of the data
+        let data_slice = &data[8..];
+        
+        // Try to deserialize from the entire remaining data
+        T::deserialize(&mut &data_slice[..])
+            .map_err(|e| CnctdSolanaError::Deserialization(e.to_string()))
     }
 
     pub async fn get_accounts_by_fields<T: FilterableAccount + BorshDeserialize>(
         &self,
         program_id: Pubkey,
         field_filters: &[(&str, serde_json::Value)],
-    ) -> anyhow::Result<Vec<(Pubkey, T)>> {
+    ) -> Result<Vec<(Pubkey, T)>> {
+        self.get_accounts_by_fields_with_commitment(program_id, field_filters, self.commitment).await
+    }
+
+    pub async fn get_accounts_by_fields_with_commitment<T: FilterableAccount + BorshDeserialize>(
+        &self,
+        program_id: Pubkey,
+        field_filters: &[(&str, serde_json::Value)],
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<(Pubkey, T)>> {
+        let filters = Self::field_filters::<T>(field_filters)?;
+        
+        // Get accounts with filters
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: None,
+                min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
+                commitment: Some(commitment),
+            },
+            with_context: None,
+            sort_results: None,
+        };
+        let all_accounts = self.pool.call("get_program_accounts", |transport| {
+            let config = config.clone();
+            async move { transport.get_program_accounts_with_config(&program_id, config).await }
+        }).await?;
+        
+        // Deserialize results
+        let mut results = Vec::new();
+        for (pubkey, account) in all_accounts {
+            if account.data.len() <= 8 {
+                continue;
+            }
+            
+            match Self::decode_account_data::<T>(&account.data) {
+                Ok(data) => {
+                    results.push((pubkey, data));
+                },
+                Err(e) => {
+                    tracing::warn!(%pubkey, error = %e, "Skipping account that failed to deserialize");
+                }
+            }
+        }
+        
+        Ok(results)
+    }
+
+    /// Discriminator and memcmp filters selecting accounts of type `T` whose
+    /// fields equal `field_filters`
+    fn field_filters<T: FilterableAccount>(field_filters: &[(&str, serde_json::Value)]) -> Result<Vec<RpcFilterType>> {
         // Start with the discriminator filter
         let mut filters = vec![
             // Filter by discriminator
@@ -170,60 +557,218 @@ impl CnctdSolana {
         // Add a filter for each field
         for (field_name, field_value) in field_filters {
             let offset = T::get_field_offset(field_name)
-                .ok_or_else(|| anyhow!("Field {} not found or not filterable", field_name))?;
+                .ok_or_else(|| CnctdSolanaError::InvalidInput(format!("Field {} not found or not filterable", field_name)))?;
             
             let value_bytes = T::serialize_field_value(field_name, field_value)
-                .ok_or_else(|| anyhow!("Could not serialize value for field {}", field_name))?;
+                .ok_or_else(|| CnctdSolanaError::InvalidInput(format!("Could not serialize value for field {}", field_name)))?;
             
             // Add filter for this field
             filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, &value_bytes)));
         }
-        
-        // Get accounts with filters
-        let all_accounts = self.client.get_program_accounts_with_config(
-            &program_id,
-            RpcProgramAccountsConfig {
-                filters: Some(filters),
-                account_config: RpcAccountInfoConfig {
+
+        Ok(filters)
+    }
+    
+    /// Streams `pubkey`'s decoded data each time the account changes. After
+    /// a dropped connection the account is re-fetched once so changes made
+    /// while disconnected are not missed. A closed account is reported as
+    /// `AccountNotFound`.
+    pub fn subscribe_account<T: BorshDeserialize + Send + 'static>(&self, pubkey: Pubkey) -> Subscription<Result<T>> {
+        self.subscribe_account_with_commitment(pubkey, self.commitment)
+    }
+
+    pub fn subscribe_account_with_commitment<T: BorshDeserialize + Send + 'static>(
+        &self,
+        pubkey: Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Subscription<Result<T>> {
+        self.account_subscription(pubkey, commitment, move |update| {
+            update.and_then(|(_slot, account)| Self::decode_account_update(pubkey, account))
+        })
+    }
+
+    /// Account notifications as `(slot, account)`, with `None` for a closed
+    /// account, passed through `map`
+    fn account_subscription<U, M>(&self, pubkey: Pubkey, commitment: CommitmentConfig, map: M) -> Subscription<U>
+    where
+        U: Send + 'static,
+        M: Fn(Result<(u64, Option<Account>)>) -> U + Clone + Send + Sync + 'static,
+    {
+        let pool = self.pool.clone();
+        let slot_tracker = self.slot_tracker.clone();
+
+        Subscription::spawn("account", self.pool.ws_urls(), move |client, sender, resubscribe| {
+            let pool = pool.clone();
+            let slot_tracker = slot_tracker.clone();
+            let map = map.clone();
+
+            async move {
+                let config = RpcAccountInfoConfig {
                     encoding: Some(UiAccountEncoding::Base64),
                     data_slice: None,
                     min_context_slot: None,
-                    commitment: None,
-                },
-                with_context: None,
-                sort_results: None,
-            }
-        ).await?;
-        
-        // Deserialize results
-        let mut results = Vec::new();
-        for (pubkey, account) in all_accounts {
-            if account.data.len() <= 8 {
-                continue;
+                    commitment: Some(commitment),
+                };
+                let (mut updates, _unsubscribe) = client.account_subscribe(&pubkey, Some(config.clone())).await?;
+
+                if resubscribe {
+                    let current = pool.call("get_account", |transport| {
+                        let config = config.clone();
+                        async move { transport.get_account_with_config(&pubkey, config).await }
+                    }).await;
+
+                    if let Ok(response) = current {
+                        slot_tracker.observe(commitment, response.context.slot);
+                        let account = response.value.filter(|account| account.lamports > 0);
+                        if sender.send(map(Ok((response.context.slot, account)))).is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+
+                while let Some(update) = updates.next().await {
+                    slot_tracker.observe(commitment, update.context.slot);
+                    let account = Self::decode_ui_account(&update.value)
+                        .map(|account| (update.context.slot, Some(account).filter(|account| account.lamports > 0)));
+                    if sender.send(map(account)).is_err() {
+                        break;
+                    }
+                }
+
+                Ok(())
             }
-            
-            match self.get_account_data::<T>(pubkey).await {
-                Ok(data) => {
-                    results.push((pubkey, data));
-                },
-                Err(e) => {
-                    println!("Error deserializing account {}: {}", pubkey, e);
+        })
+    }
+
+    /// Streams every account of type `T` owned by `program_id` whose fields
+    /// match `field_filters`, using the same filters as
+    /// `get_accounts_by_fields`, each time one of them changes. After a
+    /// dropped connection the full matching set is re-fetched and emitted.
+    pub fn subscribe_accounts_by_fields<T: FilterableAccount + BorshDeserialize + Send + 'static>(
+        &self,
+        program_id: Pubkey,
+        field_filters: &[(&str, serde_json::Value)],
+    ) -> Result<Subscription<Result<(Pubkey, T)>>> {
+        self.subscribe_accounts_by_fields_with_commitment(program_id, field_filters, self.commitment)
+    }
+
+    pub fn subscribe_accounts_by_fields_with_commitment<T: FilterableAccount + BorshDeserialize + Send + 'static>(
+        &self,
+        program_id: Pubkey,
+        field_filters: &[(&str, serde_json::Value)],
+        commitment: CommitmentConfig,
+    ) -> Result<Subscription<Result<(Pubkey, T)>>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(Self::field_filters::<T>(field_filters)?),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: None,
+                min_context_slot: None,
+                commitment: Some(commitment),
+            },
+            with_context: None,
+            sort_results: None,
+        };
+        let pool = self.pool.clone();
+
+        Ok(Subscription::spawn("program", self.pool.ws_urls(), move |client, sender, resubscribe| {
+            let pool = pool.clone();
+            let config = config.clone();
+
+            async move {
+                let (mut updates, _unsubscribe) = client.program_subscribe(&program_id, Some(config.clone())).await?;
+
+                if resubscribe {
+                    let current = pool.call("get_program_accounts", |transport| {
+                        let config = config.clone();
+                        async move { transport.get_program_accounts_with_config(&program_id, config).await }
+                    }).await;
+
+                    for (pubkey, account) in current.unwrap_or_default() {
+                        let update = Self::decode_account_data(&account.data).map(|data| (pubkey, data));
+                        if sender.send(update).is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+
+                while let Some(update) = updates.next().await {
+                    let keyed = update.value;
+                    let decoded = Pubkey::from_str(&keyed.pubkey)
+                        .map_err(|e| CnctdSolanaError::Deserialization(format!("Invalid pubkey in notification: {}", e)))
+                        .and_then(|pubkey| {
+                            let account = Self::decode_ui_account(&keyed.account)?;
+                            Ok((pubkey, Self::decode_account_data(&account.data)?))
+                        });
+
+                    if sender.send(decoded).is_err() {
+                        break;
+                    }
                 }
+
+                Ok(())
             }
+        }))
+    }
+
+    fn decode_ui_account(account: &UiAccount) -> Result<Account> {
+        account.decode::<Account>()
+            .ok_or_else(|| CnctdSolanaError::Deserialization("Unsupported account encoding in notification".to_string()))
+    }
+
+    fn decode_account_update<T: BorshDeserialize>(pubkey: Pubkey, account: Option<Account>) -> Result<T> {
+        match account {
+            Some(account) => Self::decode_account_data(&account.data),
+            None => Err(CnctdSolanaError::AccountNotFound(pubkey)),
         }
-        
-        Ok(results)
     }
-    
+
+    /// Signs, sends and waits for `confirmed`, whatever the instance commitment
     pub async fn sign_and_confirm_transaction(
         &self,
         transaction: &Transaction,
         additional_signers: Option<&[&Keypair]>,
     ) -> Result<Signature> {
-        let client = &self.client;
-        
+        self.sign_and_confirm_transaction_with_commitment(transaction, additional_signers, CommitmentConfig::confirmed()).await
+    }
+
+    /// Sends at the instance commitment and waits for `commitment`
+    pub async fn sign_and_confirm_transaction_with_commitment(
+        &self,
+        transaction: &Transaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let (signature, last_valid_block_height) = self.sign_and_send_transaction(transaction, additional_signers).await?;
+
+        match self.confirm_transaction(&signature, last_valid_block_height, commitment).await? {
+            ConfirmationOutcome::Confirmed(..) => Ok(signature),
+            ConfirmationOutcome::Failed(err) => Err(err.into()),
+            ConfirmationOutcome::Expired => Err(CnctdSolanaError::BlockhashExpired),
+        }
+    }
+
+    /// Signs `transaction` with a fresh blockhash and sends it without
+    /// waiting, returning its signature and the last block height at which
+    /// it can land, for use with `confirm_transaction`.
+    pub async fn sign_and_send_transaction(
+        &self,
+        transaction: &Transaction,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<(Signature, u64)> {
+        self.sign_and_send_transaction_with_commitment(transaction, additional_signers, self.commitment).await
+    }
+
+    /// Takes the blockhash and runs preflight at `commitment`
+    #[tracing::instrument(level = "debug", skip_all, fields(signature = tracing::field::Empty))]
+    pub async fn sign_and_send_transaction_with_commitment(
+        &self,
+        transaction: &Transaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<(Signature, u64)> {
         // Get a recent blockhash
-        let recent_blockhash = client.get_latest_blockhash().await?;
+        let (recent_blockhash, last_valid_block_height) = self.latest_blockhash(commitment).await?;
         
         // Clone transaction and set the blockhash
         let mut signed_transaction = transaction.clone();
@@ -231,32 +776,50 @@ impl CnctdSolana {
         
         // Get the primary signer
         let signer_keypair = self.signer_keypair.as_ref()
-            .ok_or_else(|| anyhow!("Signer keypair is not set"))?;
+            .ok_or(CnctdSolanaError::MissingSigner)?;
         
         // Sign with just the primary signer or with additional signers
         if let Some(additional) = additional_signers {
             let mut all_signers = vec![signer_keypair];
             all_signers.extend(additional.iter());
             
-            let all_signers_refs: Vec<&Keypair> = all_signers.iter().map(|k| *k).collect();
-            signed_transaction.sign(&all_signers_refs, recent_blockhash);
+            signed_transaction.sign(&all_signers, recent_blockhash);
         } else {
             signed_transaction.sign(&[signer_keypair], recent_blockhash);
         }
         
-        // Send and confirm the signed transaction
-        let signature = client.send_and_confirm_transaction(&signed_transaction).await?;
+        // Send the signed transaction
+        let config = RpcSendTransactionConfig {
+            preflight_commitment: Some(commitment.commitment),
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
+            ..RpcSendTransactionConfig::default()
+        };
+        let signed_transaction = &signed_transaction;
+        let signature = self.pool.call("send_transaction", |transport| async move {
+            transport.send_transaction_with_config(signed_transaction, config).await
+        }).await?;
+        tracing::Span::current().record("signature", tracing::field::display(&signature));
         
-        Ok(signature)
+        Ok((signature, last_valid_block_height))
     }
     
+    /// Simulates at `confirmed`, whatever the instance commitment, so that
+    /// recent blockhashes are found
     pub async fn simulate_transaction(
         &self,
         transaction: &Transaction,
         additional_signers: Option<&[&Keypair]>,
     ) -> Result<solana_client::rpc_response::RpcSimulateTransactionResult> {
-        let client = &self.client;
-        let recent_blockhash = client.get_latest_blockhash().await?;
+        self.simulate_transaction_with_commitment(transaction, additional_signers, CommitmentConfig::confirmed()).await
+    }
+
+    pub async fn simulate_transaction_with_commitment(
+        &self,
+        transaction: &Transaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<solana_client::rpc_response::RpcSimulateTransactionResult> {
+        let (recent_blockhash, _last_valid_block_height) = self.latest_blockhash(commitment).await?;
         
         // Clone the transaction and update blockhash
         let mut signed_transaction = transaction.clone();
@@ -273,55 +836,30 @@ impl CnctdSolana {
                 signed_transaction.sign(&[signer], recent_blockhash);
             }
         } else {
-            return Err(anyhow!("No signer keypair provided for transaction simulation"));
+            return Err(CnctdSolanaError::MissingSigner);
         }
         
         // Use simulation config to handle PDA creation scenarios
         let config = RpcSimulateTransactionConfig {
             sig_verify: false,
             replace_recent_blockhash: true,
-            commitment: Some(CommitmentConfig::confirmed()),
+            commitment: Some(commitment),
             accounts: None,
             encoding: None,
-            min_context_slot: None,
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
             inner_instructions: true,
         };
         
-        let simulation_result = client.simulate_transaction_with_config(
-            &signed_transaction, 
-            config
-        ).await?;
+        let signed_transaction = &signed_transaction;
+        let simulation_result = self.pool.call("simulate_transaction", |transport| {
+            let config = config.clone();
+            async move { transport.simulate_transaction_with_config(signed_transaction, config).await }
+        }).await?;
+        self.slot_tracker.observe(commitment, simulation_result.context.slot);
         
         Ok(simulation_result.value)
     }
     
-    pub async fn estimate_transaction_fee(
-        &self,
-        transaction: &Transaction,
-        additional_signers: Option<&[&Keypair]>,
-    ) -> anyhow::Result<u64> {
-        // Simulate the transaction to get compute units
-        let simulation = self.simulate_transaction(transaction, additional_signers).await?;
-        
-        // Base fee: 5,000 lamports per signature
-        let base_fee = transaction.signatures.len() as u64 * 5_000;
-        
-        // Extract units consumed from the simulation
-        let compute_units_consumed = simulation.units_consumed.unwrap_or(0);
-        
-        // Compute unit fee calculation
-        let compute_unit_price = 5; // micro-lamports per compute unit (5 / 10^6)
-        let compute_fee = (compute_units_consumed as u128 * compute_unit_price as u128 / 1_000_000) as u64;
-        
-        // Total fee
-        let total_fee = base_fee + compute_fee;
-        
-        println!("Estimated fee: {} lamports (base: {}, compute: {}, units: {})",
-                 total_fee, base_fee, compute_fee, compute_units_consumed);
-        
-        Ok(total_fee)
-    }
-
     pub fn get_discriminator(instruction_name: &str) -> [u8; 8] {
         let mut hasher = Sha256::new();
         hasher.update(format!("global:{}", instruction_name).as_bytes());
@@ -331,33 +869,149 @@ impl CnctdSolana {
         discriminator
     }
     
-    pub async fn wait_for_confirmation(&self, signature: &Signature, max_retries: Option<u32>) -> anyhow::Result<bool> {
-        let max_retries = max_retries.unwrap_or(60); // Default to 30 retries
-        let rpc_client = &self.client;
-        let mut attempts = 0;
-        let sleep_time = std::time::Duration::from_millis(500); // 500ms between checks
-        
-        while attempts < max_retries {
-            match rpc_client.confirm_transaction(signature).await {
-                Ok(_) => {
-                    return Ok(true); // Transaction confirmed successfully
-                },
-                Err(e) => {
-                    // Check if the error indicates the transaction is still in flight
-                    if e.to_string().contains("not found") || e.to_string().contains("still in flight") {
-                        // Transaction not yet processed, continue waiting
-                        attempts += 1;
-                        tokio::time::sleep(sleep_time).await;
-                    } else {
-                        // Other RPC error, likely a failure
-                        return Err(anyhow::anyhow!("Transaction confirmation failed: {}", e));
+    /// Waits for `signature` to reach `commitment`, or for the chain to pass
+    /// `last_valid_block_height` (from the blockhash it was signed with)
+    /// without it landing.
+    ///
+    /// Listens with `signatureSubscribe` and polls signature statuses
+    /// alongside, so a dropped or unavailable websocket only slows it down.
+    #[tracing::instrument(level = "debug", skip_all, fields(%signature, last_valid_block_height, commitment = ?commitment.commitment))]
+    pub async fn confirm_transaction(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+        commitment: CommitmentConfig,
+    ) -> Result<ConfirmationOutcome> {
+        self.confirm_until_expired(signature, commitment, || async move {
+            let block_height = self.pool.call("get_block_height", |transport| async move {
+                transport.get_block_height_with_commitment(commitment).await
+            }).await?;
+
+            Ok(block_height > last_valid_block_height)
+        }).await
+    }
+
+    /// The wait behind `confirm_transaction` and `confirm_nonce_transaction`.
+    /// `expired` is checked on each poll that finds no status; once it
+    /// returns `true` the status is checked one last time, since the
+    /// transaction may have landed in between, before reporting `Expired`.
+    pub(crate) async fn confirm_until_expired<F, Fut>(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+        expired: F,
+    ) -> Result<ConfirmationOutcome>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<bool>>,
+    {
+        let mut notifications = self.subscribe_signature(*signature, commitment);
+        let mut poll = tokio::time::interval(CONFIRMATION_POLL_INTERVAL);
+
+        loop {
+            tokio::select! {
+                Some(outcome) = notifications.next() => {
+                    tracing::debug!(?outcome, "Confirmation notified");
+                    self.observe_outcome(&outcome);
+                    return Ok(outcome);
+                }
+                _ = poll.tick() => {
+                    // A failed poll is tried again on the next tick; only
+                    // expiry ends the wait without an outcome
+                    match self.signature_outcome(signature, commitment).await {
+                        Ok(Some(outcome)) => return Ok(outcome),
+                        Ok(None) => {}
+                        Err(e) => {
+                            tracing::warn!(error = %e, "Signature status poll failed");
+                            continue;
+                        }
+                    }
+
+                    match expired().await {
+                        Ok(true) => {
+                            let outcome = self.signature_outcome(signature, commitment).await?;
+                            return Ok(outcome.unwrap_or(ConfirmationOutcome::Expired));
+                        }
+                        Ok(false) => {}
+                        Err(e) => tracing::warn!(error = %e, "Expiry poll failed"),
                     }
                 }
             }
         }
+    }
+
+    /// Polls `signature` until it confirms, for up to `max_retries` polls
+    /// (60 by default) 500ms apart.
+    #[deprecated(note = "use `confirm_transaction`, which can tell an expired transaction from a slow one")]
+    pub async fn wait_for_confirmation(&self, signature: &Signature, max_retries: Option<u32>) -> Result<bool> {
+        let max_retries = max_retries.unwrap_or(60);
+
+        for _ in 0..max_retries {
+            match self.signature_outcome(signature, CommitmentConfig::confirmed()).await? {
+                Some(ConfirmationOutcome::Failed(err)) => return Err(err.into()),
+                Some(_) => return Ok(true),
+                None => tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await,
+            }
+        }
         
         // If we reach here, we've exceeded max_retries
-        Err(anyhow::anyhow!("Transaction confirmation timed out after {} attempts", max_retries))
+        Err(CnctdSolanaError::Timeout(format!("Transaction confirmation timed out after {} attempts", max_retries)))
+    }
+
+    pub(crate) async fn signature_outcome(&self, signature: &Signature, commitment: CommitmentConfig) -> Result<Option<ConfirmationOutcome>> {
+        let signatures = [*signature];
+        let statuses = self.pool.call("get_signature_statuses", |transport| {
+            let signatures = &signatures;
+            async move { transport.get_signature_statuses(signatures).await }
+        }).await?;
+
+        let outcome = statuses.value
+            .into_iter()
+            .next()
+            .flatten()
+            .and_then(|status| ConfirmationOutcome::from_status(status, commitment.commitment));
+
+        if let Some(outcome) = &outcome {
+            self.observe_outcome(outcome);
+        }
+
+        Ok(outcome)
+    }
+
+    /// A confirmed transaction's slot has been reached at its commitment,
+    /// so later reads at that commitment must include it
+    fn observe_outcome(&self, outcome: &ConfirmationOutcome) {
+        if let ConfirmationOutcome::Confirmed(commitment, slot) = outcome {
+            self.slot_tracker.observe(CommitmentConfig { commitment: *commitment }, *slot);
+        }
+    }
+
+    /// Yields a single outcome once the signature reaches `commitment`
+    pub(crate) fn subscribe_signature(&self, signature: Signature, commitment: CommitmentConfig) -> Subscription<ConfirmationOutcome> {
+        Subscription::spawn("signature", self.pool.ws_urls(), move |client, sender, _resubscribe| async move {
+            let config = RpcSignatureSubscribeConfig {
+                commitment: Some(commitment),
+                enable_received_notification: Some(false),
+            };
+            let (mut notifications, _unsubscribe) = client.signature_subscribe(&signature, Some(config)).await?;
+
+            while let Some(notification) = notifications.next().await {
+                if let RpcSignatureResult::ProcessedSignature(result) = notification.value {
+                    let outcome = match result.err {
+                        Some(err) => ConfirmationOutcome::Failed(err.into()),
+                        None => ConfirmationOutcome::Confirmed(commitment.commitment, notification.context.slot),
+                    };
+
+                    if sender.send(outcome).is_ok() {
+                        // Hold the connection until the waiter has what it needs
+                        sender.closed().await;
+                    }
+                    break;
+                }
+            }
+
+            Ok(())
+        })
     }
     
 }
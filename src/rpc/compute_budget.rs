@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_commitment_config::CommitmentConfig;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::{instruction::Instruction, message::AddressLookupTableAccount, pubkey::Pubkey, transaction::TransactionError};
+
+use crate::rpc::{
+    client::CnctdSolana,
+    error::{CnctdSolanaError, Result},
+    ComputeUnitLimit, PriorityFee,
+};
+
+/// The most compute units a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Compute budget instructions for the transaction builders to add.
+///
+/// With a `Simulated` limit the transaction is simulated first and asks for
+/// the units it consumed plus `margin_percent`, so it neither pays for
+/// unused compute nor fails when state shifts slightly before it lands.
+///
+/// Named priority fees are priced from the recent fees paid to write the
+/// same accounts, as `estimate_transaction_fee` prices them, see
+/// `PriorityFeeTiers`. `Custom` is used as given.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudgetConfig {
+    pub unit_limit: ComputeUnitLimit,
+    pub priority_fee: PriorityFee,
+    /// Added to the simulated units, in percent
+    pub margin_percent: u32,
+}
+
+impl Default for ComputeBudgetConfig {
+    fn default() -> Self {
+        Self {
+            unit_limit: ComputeUnitLimit::Simulated,
+            priority_fee: PriorityFee::None,
+            margin_percent: 10,
+        }
+    }
+}
+
+impl ComputeBudgetConfig {
+    pub fn with_unit_limit(mut self, unit_limit: ComputeUnitLimit) -> Self {
+        self.unit_limit = unit_limit;
+        self
+    }
+
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    pub fn with_margin_percent(mut self, margin_percent: u32) -> Self {
+        self.margin_percent = margin_percent;
+        self
+    }
+
+    /// Limit to request for a transaction that consumed `units_consumed`
+    pub fn limit_for(&self, units_consumed: u64) -> u32 {
+        let limit = (units_consumed * (100 + self.margin_percent as u64)).div_ceil(100);
+
+        limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+    }
+}
+
+impl CnctdSolana {
+    /// Adds compute budget instructions to every transaction built by
+    /// `create_unsigned_transaction` and `create_unsigned_versioned_transaction`
+    pub fn with_compute_budget(mut self, config: ComputeBudgetConfig) -> Self {
+        self.compute_budget = Some(config);
+        self
+    }
+
+    /// `instructions` led by the compute budget instructions `config` asks
+    /// for, replacing any already there. `lookup_tables` are only used to
+    /// compile the simulation for a `Simulated` limit.
+    pub async fn apply_compute_budget(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+        config: &ComputeBudgetConfig,
+    ) -> Result<Vec<Instruction>> {
+        let instructions: Vec<Instruction> = instructions
+            .iter()
+            .filter(|instruction| instruction.program_id != solana_compute_budget_interface::id())
+            .cloned()
+            .collect();
+
+        let unit_limit = match config.unit_limit {
+            ComputeUnitLimit::Simulated => {
+                let units_consumed = self.simulate_compute_units(payer, &instructions, lookup_tables).await?;
+                tracing::debug!(units_consumed, "Simulated compute units");
+
+                Some(config.limit_for(units_consumed))
+            }
+            unit_limit => unit_limit.to_limit(),
+        };
+
+        let unit_price = self.compute_unit_price(payer, &instructions, config.priority_fee).await?;
+
+        let mut budgeted = compute_budget_instructions(unit_limit, unit_price);
+        budgeted.extend(instructions);
+
+        Ok(budgeted)
+    }
+
+    /// Units consumed by `instructions`, simulated at the maximum limit so
+    /// that heavy transactions are measured rather than cut off. No price is
+    /// set: at the maximum limit it would charge the payer for 1.4M units,
+    /// which can fail a payer that affords the measured limit. Simulated at
+    /// `confirmed`, as a finalized bank may not know a recent blockhash.
+    async fn simulate_compute_units(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<u64> {
+        let mut simulated = compute_budget_instructions(Some(MAX_COMPUTE_UNIT_LIMIT), 0);
+        simulated.extend_from_slice(instructions);
+
+        // Unsigned, as the signers may not be at hand until sending
+        let transaction = Self::compile_versioned_transaction(payer, &simulated, lookup_tables)?;
+        let commitment = CommitmentConfig::confirmed();
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(commitment),
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
+            ..RpcSimulateTransactionConfig::default()
+        };
+
+        let transaction = &transaction;
+        let simulation_result = self.pool.call("simulate_transaction", |transport| {
+            let config = config.clone();
+            async move { transport.simulate_versioned_transaction_with_config(transaction, config).await }
+        }).await?;
+        self.slot_tracker.observe(commitment, simulation_result.context.slot);
+
+        if let Some(err) = simulation_result.value.err {
+            return Err(TransactionError::from(err).into());
+        }
+
+        simulation_result.value.units_consumed
+            .ok_or_else(|| CnctdSolanaError::Deserialization("simulation did not report units consumed".to_string()))
+    }
+
+    /// Micro-lamports per unit for `priority_fee`, reading the tiers for the
+    /// accounts `instructions` write to when it names one
+    async fn compute_unit_price(&self, payer: &Pubkey, instructions: &[Instruction], priority_fee: PriorityFee) -> Result<u64> {
+        match priority_fee {
+            PriorityFee::None => Ok(0),
+            PriorityFee::Custom(micro_lamports) => Ok(micro_lamports),
+            priority_fee => {
+                let mut writable = vec![*payer];
+                for account in instructions.iter().flat_map(|instruction| &instruction.accounts).filter(|account| account.is_writable) {
+                    if !writable.contains(&account.pubkey) {
+                        writable.push(account.pubkey);
+                    }
+                }
+
+                let tiers = self.get_priority_fee_tiers(&writable).await?;
+                Ok(tiers.micro_lamports(priority_fee))
+            }
+        }
+    }
+}
+
+fn compute_budget_instructions(unit_limit: Option<u32>, unit_price: u64) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(2);
+
+    if let Some(unit_limit) = unit_limit {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(unit_limit));
+    }
+
+    if unit_price > 0 {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(unit_price));
+    }
+
+    instructions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use borsh::BorshDeserialize;
+    use solana_sdk::message::VersionedMessage;
+    use solana_system_interface::instruction as system_instruction;
+
+    use super::*;
+    use crate::rpc::{MemoryTransport, RpcEndpoint, RpcMode, RpcPool};
+
+    fn client(memory: &Arc<MemoryTransport>) -> CnctdSolana {
+        let endpoint = RpcEndpoint::with_transport(memory.clone());
+
+        CnctdSolana::with_pool(RpcPool::from_endpoints(vec![endpoint], RpcMode::Failover)).unwrap()
+    }
+
+    fn budget_instructions<'a>(instructions: impl IntoIterator<Item = &'a Instruction>) -> Vec<ComputeBudgetInstruction> {
+        instructions
+            .into_iter()
+            .filter(|instruction| instruction.program_id == solana_compute_budget_interface::id())
+            .map(|instruction| ComputeBudgetInstruction::try_from_slice(&instruction.data).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn simulates_without_a_price_and_prices_the_measured_limit() {
+        let memory = Arc::new(MemoryTransport::new());
+        memory.set_units_consumed(10_000);
+        let client = client(&memory);
+        let payer = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+
+        let config = ComputeBudgetConfig::default().with_priority_fee(PriorityFee::Custom(1_000));
+        let instructions = client.apply_compute_budget(&payer, &[transfer], &[], &config).await.unwrap();
+
+        assert_eq!(budget_instructions(&instructions), vec![
+            ComputeBudgetInstruction::SetComputeUnitLimit(11_000),
+            ComputeBudgetInstruction::SetComputeUnitPrice(1_000),
+        ]);
+
+        let simulated = memory.simulated_versioned_transactions();
+        let VersionedMessage::V0(message) = &simulated[0].message else { panic!("simulated a v0 message") };
+        let program_ids = message.instructions.iter().map(|instruction| message.account_keys[instruction.program_id_index as usize]);
+        assert_eq!(program_ids.filter(|program_id| *program_id == solana_compute_budget_interface::id()).count(), 1);
+        assert_eq!(message.instructions[0].data, ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT).data);
+    }
+
+    #[tokio::test]
+    async fn prices_named_levels_from_recent_fees() {
+        let memory = Arc::new(MemoryTransport::new());
+        memory.set_prioritization_fees(vec![100, 300, 500, 700]);
+        let client = client(&memory);
+        let payer = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+
+        let config = ComputeBudgetConfig::default()
+            .with_unit_limit(ComputeUnitLimit::Custom(50_000))
+            .with_priority_fee(PriorityFee::High);
+        let instructions = client.apply_compute_budget(&payer, &[transfer], &[], &config).await.unwrap();
+
+        // The 75th percentile tier, not a fixed price
+        assert_eq!(budget_instructions(&instructions), vec![
+            ComputeBudgetInstruction::SetComputeUnitLimit(50_000),
+            ComputeBudgetInstruction::SetComputeUnitPrice(500),
+        ]);
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use solana_commitment_config::CommitmentLevel;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+
+/// How often signature statuses and the block height are polled while
+/// waiting on a confirmation
+pub const CONFIRMATION_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Final state of a sent transaction
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum ConfirmationOutcome {
+    /// Landed and reached at least the requested commitment, in this slot
+    Confirmed(CommitmentLevel, u64),
+    /// Landed but failed; it will not succeed if sent again unchanged
+    Failed(TransactionError),
+    /// Its blockhash expired, or for a durable nonce transaction its nonce
+    /// advanced, without it landing; safe to re-sign and re-send
+    Expired,
+}
+
+impl ConfirmationOutcome {
+    /// Outcome for a polled status, or `None` while it has not yet reached
+    /// `commitment`
+    pub(crate) fn from_status(status: TransactionStatus, commitment: CommitmentLevel) -> Option<Self> {
+        if let Some(err) = status.err {
+            return Some(Self::Failed(err));
+        }
+
+        let reached = match status.confirmation_status {
+            Some(TransactionConfirmationStatus::Finalized) => CommitmentLevel::Finalized,
+            Some(TransactionConfirmationStatus::Confirmed) => CommitmentLevel::Confirmed,
+            Some(TransactionConfirmationStatus::Processed) => CommitmentLevel::Processed,
+            // Nodes that predate `confirmationStatus` report rooted transactions without confirmations
+            None if status.confirmations.is_none() => CommitmentLevel::Finalized,
+            None => CommitmentLevel::Confirmed,
+        };
+
+        (rank(reached) >= rank(commitment)).then_some(Self::Confirmed(reached, status.slot))
+    }
+}
+
+fn rank(commitment: CommitmentLevel) -> u8 {
+    match commitment {
+        CommitmentLevel::Processed => 0,
+        CommitmentLevel::Confirmed => 1,
+        CommitmentLevel::Finalized => 2,
+    }
+}
@@ -0,0 +1,193 @@
+use std::{str::FromStr, time::Duration};
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_custom_error::{JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY},
+    rpc_request::RpcError,
+};
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, transaction::TransactionError};
+use thiserror::Error;
+
+use crate::rpc::rate_limit::RateLimitedResponse;
+
+pub type Result<T> = std::result::Result<T, CnctdSolanaError>;
+
+/// JSON-RPC error codes providers use to signal rate limiting
+const RATE_LIMIT_ERROR_CODES: [i64; 2] = [429, -32005];
+
+/// JSON-RPC error codes for a condition of the node rather than the
+/// request, which another node may not share
+const NODE_ERROR_CODES: [i64; 2] = [JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY, JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED];
+
+#[derive(Debug, Error)]
+pub enum CnctdSolanaError {
+    #[error("blockhash expired or not found")]
+    BlockhashExpired,
+
+    /// A durable nonce transaction's nonce moved on without it landing
+    #[error("nonce advanced before the transaction landed")]
+    NonceAdvanced,
+
+    #[error("insufficient funds: {0}")]
+    InsufficientFunds(String),
+
+    #[error("account not found: {0}")]
+    AccountNotFound(Pubkey),
+
+    #[error("failed to deserialize account data: {0}")]
+    Deserialization(String),
+
+    #[error("failed to serialize: {0}")]
+    Serialization(String),
+
+    /// Anchor / program `Custom` error, e.g. an Anchor `#[error_code]` value
+    #[error("custom program error {code:#x} in instruction {instruction_index}")]
+    ProgramError { instruction_index: u8, code: u32 },
+
+    #[error("transaction failed: {0}")]
+    TransactionFailed(TransactionError),
+
+    #[error("rate limited{}", retry_after.map(|d| format!(", retry after {:?}", d)).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("timed out: {0}")]
+    Timeout(String),
+
+    /// The node is behind the `min_context_slot` the read asked for
+    #[error("node has not reached the minimum context slot")]
+    MinContextSlotNotReached,
+
+    #[error("transport error: {0}")]
+    Transport(#[source] Box<ClientError>),
+
+    #[error("signer keypair is not set")]
+    MissingSigner,
+
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("configuration error: {0}")]
+    Config(String),
+}
+
+impl CnctdSolanaError {
+    /// Whether the same request could succeed if retried, possibly on
+    /// another endpoint. Only failures on the node's side (connection
+    /// errors, HTTP 5xx, unhealthy or lagging nodes, rate limits) return
+    /// `true`; errors about the request itself, such as invalid params or
+    /// an unknown method, would get the same answer anywhere.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::Timeout(_) | Self::MinContextSlotNotReached => true,
+            Self::Transport(error) => match error.kind() {
+                // Without a status the request never got an answer
+                ClientErrorKind::Reqwest(e) => e.status().is_none_or(|status| status.is_server_error()),
+                ClientErrorKind::Io(_)
+                | ClientErrorKind::Middleware(_)
+                | ClientErrorKind::SerdeJson(_)
+                | ClientErrorKind::Custom(_) => true,
+                ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+                    NODE_ERROR_CODES.contains(code) || RATE_LIMIT_ERROR_CODES.contains(code)
+                }
+                // A response that could not be parsed came from a misbehaving node
+                ClientErrorKind::RpcError(RpcError::ParseError(_)) => true,
+                ClientErrorKind::RpcError(RpcError::RpcRequestError(_) | RpcError::ForUser(_)) => false,
+                ClientErrorKind::SigningError(_) | ClientErrorKind::TransactionError(_) => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl From<TransactionError> for CnctdSolanaError {
+    fn from(error: TransactionError) -> Self {
+        match error {
+            TransactionError::BlockhashNotFound => Self::BlockhashExpired,
+            TransactionError::InsufficientFundsForFee => Self::InsufficientFunds("insufficient funds for fee".to_string()),
+            TransactionError::InsufficientFundsForRent { account_index } => {
+                Self::InsufficientFunds(format!("insufficient funds for rent in account {}", account_index))
+            }
+            // Returned when the fee payer has never been funded
+            TransactionError::AccountNotFound => Self::InsufficientFunds("fee payer account not found".to_string()),
+            TransactionError::InstructionError(index, InstructionError::InsufficientFunds) => {
+                Self::InsufficientFunds(format!("insufficient funds in instruction {}", index))
+            }
+            TransactionError::InstructionError(instruction_index, InstructionError::Custom(code)) => {
+                Self::ProgramError { instruction_index, code }
+            }
+            error => Self::TransactionFailed(error),
+        }
+    }
+}
+
+impl From<ClientError> for CnctdSolanaError {
+    fn from(error: ClientError) -> Self {
+        if let Some(transaction_error) = error.get_transaction_error() {
+            return transaction_error.into();
+        }
+
+        match error.kind() {
+            ClientErrorKind::Reqwest(e) if e.status().map(|status| status.as_u16()) == Some(429) => {
+                return Self::RateLimited { retry_after: None };
+            }
+            ClientErrorKind::Middleware(e) if e.is::<RateLimitedResponse>() => {
+                let retry_after = e.downcast_ref::<RateLimitedResponse>().and_then(|response| response.retry_after);
+                return Self::RateLimited { retry_after };
+            }
+            ClientErrorKind::Reqwest(e) if e.is_timeout() => {
+                return Self::Timeout(e.to_string());
+            }
+            ClientErrorKind::Io(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                return Self::Timeout(e.to_string());
+            }
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) if RATE_LIMIT_ERROR_CODES.contains(code) => {
+                return Self::RateLimited { retry_after: None };
+            }
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code: JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, .. }) => {
+                return Self::MinContextSlotNotReached;
+            }
+            ClientErrorKind::RpcError(RpcError::ForUser(message)) => {
+                // `RpcClient::get_account` reports a missing account as
+                // exactly "AccountNotFound: pubkey=<address>"; with anything
+                // after the address the request itself failed
+                if let Some(pubkey) = message.strip_prefix("AccountNotFound: pubkey=")
+                    .and_then(|address| Pubkey::from_str(address).ok())
+                {
+                    return Self::AccountNotFound(pubkey);
+                }
+            }
+            _ => {}
+        }
+
+        Self::Transport(Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response_error(code: i64) -> CnctdSolanaError {
+        ClientError::from(RpcError::RpcResponseError {
+            code,
+            message: "error".to_string(),
+            data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+        }).into()
+    }
+
+    #[test]
+    fn request_errors_are_not_retryable() {
+        // Invalid params, method not found
+        assert!(!response_error(-32602).is_retryable());
+        assert!(!response_error(-32601).is_retryable());
+        assert!(!CnctdSolanaError::from(ClientError::from(RpcError::RpcRequestError("bad request".to_string()))).is_retryable());
+    }
+
+    #[test]
+    fn node_errors_are_retryable() {
+        assert!(response_error(JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY).is_retryable());
+        assert!(response_error(JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED).is_retryable());
+        assert!(response_error(429).is_retryable());
+        assert!(CnctdSolanaError::from(ClientError::from(RpcError::ParseError("garbled".to_string()))).is_retryable());
+    }
+}
@@ -0,0 +1,218 @@
+use borsh::BorshDeserialize;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig};
+use solana_commitment_config::CommitmentConfig;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::{message::Message, pubkey::Pubkey, transaction::{Transaction, TransactionError}};
+
+use crate::rpc::{
+    client::CnctdSolana,
+    error::{CnctdSolanaError, Result},
+    PriorityFee, MAX_COMPUTE_UNIT_LIMIT,
+};
+
+/// Lamports charged per signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Units charged for each instruction of a transaction that does not set a
+/// compute unit limit
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// Percentiles of recent fees the low, medium, high and very high tiers sit at
+pub const PRIORITY_FEE_PERCENTILES: [u8; 4] = [25, 50, 75, 95];
+
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Compute unit prices, in micro-lamports, paid to land in recent slots
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PriorityFeeTiers {
+    pub low: u64,
+    pub medium: u64,
+    pub high: u64,
+    pub very_high: u64,
+}
+
+impl PriorityFeeTiers {
+    /// Tiers at `PRIORITY_FEE_PERCENTILES` of `fees`, all zero if empty
+    pub fn from_recent_fees(fees: &[u64]) -> Self {
+        let mut fees = fees.to_vec();
+        fees.sort_unstable();
+        let [low, medium, high, very_high] = PRIORITY_FEE_PERCENTILES.map(|percentile| nearest_rank(&fees, percentile));
+
+        Self { low, medium, high, very_high }
+    }
+
+    /// The price `priority_fee` stands for. Named levels map to the tiers,
+    /// `Highest` to `very_high`; `Custom` is taken as given.
+    pub fn micro_lamports(&self, priority_fee: PriorityFee) -> u64 {
+        match priority_fee {
+            PriorityFee::None => 0,
+            PriorityFee::Low => self.low,
+            PriorityFee::Medium => self.medium,
+            PriorityFee::High => self.high,
+            PriorityFee::Highest => self.very_high,
+            PriorityFee::Custom(fee) => fee,
+        }
+    }
+}
+
+fn nearest_rank(sorted: &[u64], percentile: u8) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+
+    let rank = (sorted.len() * percentile as usize).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// What a transaction costs its signers, in lamports unless noted
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// `LAMPORTS_PER_SIGNATURE` for each required signature
+    pub signature_fee: u64,
+    /// Units the priority fee is charged on: the limit the transaction sets,
+    /// or the default for each instruction
+    pub compute_unit_limit: u32,
+    /// Units the transaction used in simulation
+    pub compute_units_consumed: u64,
+    /// Micro-lamports per unit, from the transaction if it sets a price
+    pub compute_unit_price: u64,
+    pub priority_fee: u64,
+    /// Moved into accounts the transaction creates. Not a fee, and returned
+    /// when the accounts are closed, but the signers need it up front.
+    pub rent: u64,
+    /// Recent prices for transactions writing to the same accounts
+    pub tiers: PriorityFeeTiers,
+}
+
+impl FeeEstimate {
+    /// What the network charges
+    pub fn transaction_fee(&self) -> u64 {
+        self.signature_fee + self.priority_fee
+    }
+
+    /// Fees plus rent
+    pub fn total(&self) -> u64 {
+        self.transaction_fee() + self.rent
+    }
+}
+
+impl CnctdSolana {
+    /// Recent prices for transactions writing to all of `accounts`, which
+    /// should be the writable accounts of the transaction being priced
+    pub async fn get_priority_fee_tiers(&self, accounts: &[Pubkey]) -> Result<PriorityFeeTiers> {
+        let fees = self.pool.call("get_recent_prioritization_fees", |transport| async move {
+            transport.get_recent_prioritization_fees(accounts).await
+        }).await?;
+        let fees: Vec<u64> = fees.iter().map(|fee| fee.prioritization_fee).collect();
+
+        Ok(PriorityFeeTiers::from_recent_fees(&fees))
+    }
+
+    /// Prices `transaction` at the `priority_fee` tier, simulating it for
+    /// the units it uses and the accounts it creates. Signatures are not
+    /// needed. A transaction that fails in simulation returns its error.
+    /// Simulates at `confirmed`, whatever the instance commitment.
+    pub async fn estimate_transaction_fee(&self, transaction: &Transaction, priority_fee: PriorityFee) -> Result<FeeEstimate> {
+        self.estimate_transaction_fee_with_commitment(transaction, priority_fee, CommitmentConfig::confirmed()).await
+    }
+
+    pub async fn estimate_transaction_fee_with_commitment(
+        &self,
+        transaction: &Transaction,
+        priority_fee: PriorityFee,
+        commitment: CommitmentConfig,
+    ) -> Result<FeeEstimate> {
+        let message = &transaction.message;
+        let writable: Vec<Pubkey> = message.account_keys
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| message.is_maybe_writable(*index, None))
+            .map(|(_, pubkey)| *pubkey)
+            .collect();
+
+        let tiers = self.get_priority_fee_tiers(&writable).await?;
+        let existing = self.get_multiple_accounts_with_commitment(&writable, commitment).await?;
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(commitment),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: writable.iter().map(Pubkey::to_string).collect(),
+            }),
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let simulation_result = self.pool.call("simulate_transaction", |transport| {
+            let config = config.clone();
+            async move { transport.simulate_transaction_with_config(transaction, config).await }
+        }).await?;
+        self.slot_tracker.observe(commitment, simulation_result.context.slot);
+        let simulation = simulation_result.value;
+
+        if let Some(err) = simulation.err {
+            return Err(TransactionError::from(err).into());
+        }
+
+        // Lamports in accounts that only exist after the transaction
+        let rent = simulation.accounts
+            .unwrap_or_default()
+            .iter()
+            .zip(&existing)
+            .filter(|(_, before)| before.as_ref().is_none_or(|account| account.lamports == 0))
+            .filter_map(|(after, _)| after.as_ref().map(|account| account.lamports))
+            .sum();
+
+        let (requested_limit, requested_price) = requested_compute_budget(message)?;
+        let compute_unit_limit = requested_limit.unwrap_or_else(|| default_compute_unit_limit(message));
+        let compute_unit_price = requested_price.unwrap_or_else(|| tiers.micro_lamports(priority_fee));
+        let priority_fee = (compute_unit_price as u128 * compute_unit_limit as u128).div_ceil(MICRO_LAMPORTS_PER_LAMPORT) as u64;
+
+        let estimate = FeeEstimate {
+            signature_fee: message.header.num_required_signatures as u64 * LAMPORTS_PER_SIGNATURE,
+            compute_unit_limit,
+            compute_units_consumed: simulation.units_consumed.unwrap_or(0),
+            compute_unit_price,
+            priority_fee,
+            rent,
+            tiers,
+        };
+        tracing::debug!(?estimate, "Estimated transaction fee");
+
+        Ok(estimate)
+    }
+}
+
+/// The limit and price set by `message`'s compute budget instructions
+fn requested_compute_budget(message: &Message) -> Result<(Option<u32>, Option<u64>)> {
+    let mut unit_limit = None;
+    let mut unit_price = None;
+
+    for instruction in &message.instructions {
+        if message.account_keys.get(instruction.program_id_index as usize) != Some(&solana_compute_budget_interface::id()) {
+            continue;
+        }
+
+        match ComputeBudgetInstruction::try_from_slice(&instruction.data)
+            .map_err(|e| CnctdSolanaError::Deserialization(format!("compute budget instruction: {}", e)))?
+        {
+            ComputeBudgetInstruction::SetComputeUnitLimit(limit) => unit_limit = Some(limit.min(MAX_COMPUTE_UNIT_LIMIT)),
+            ComputeBudgetInstruction::SetComputeUnitPrice(price) => unit_price = Some(price),
+            _ => {}
+        }
+    }
+
+    Ok((unit_limit, unit_price))
+}
+
+fn default_compute_unit_limit(message: &Message) -> u32 {
+    let instructions = message.instructions
+        .iter()
+        .filter(|instruction| message.account_keys.get(instruction.program_id_index as usize) != Some(&solana_compute_budget_interface::id()))
+        .count() as u32;
+
+    instructions.saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT).min(MAX_COMPUTE_UNIT_LIMIT)
+}
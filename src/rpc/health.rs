@@ -0,0 +1,322 @@
+use std::{collections::VecDeque, sync::Mutex, time::{Duration, Instant}};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct HealthConfig {
+    pub window: usize,            // Number of recent calls used for latency and error rate
+    pub failure_threshold: u32,   // Consecutive failures before an endpoint is cooled down
+    pub cooldown: Duration,       // First cool-down period, doubled on each failed probe
+    pub max_cooldown: Duration,   // Upper bound for the doubled cool-down
+    pub error_penalty: f64,       // How strongly the error rate inflates an endpoint's score
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            window: 100,
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+            max_cooldown: Duration::from_secs(300),
+            error_penalty: 4.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EndpointStatus {
+    Healthy,     // Eligible and ranked by score
+    Probing,     // Cool-down elapsed; the next call doubles as a recovery probe
+    CoolingDown, // Only used when nothing better is available
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EndpointHealthSnapshot {
+    pub url: String,
+    pub domain: String,
+    pub status: EndpointStatus,
+    pub score: f64,
+    pub p50_ms: Option<u64>,
+    pub p90_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    pub error_rate: f64,
+    pub successes: u64,
+    pub errors: u64,
+    pub timeouts: u64,
+    pub consecutive_failures: u32,
+    pub cooldown_remaining_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    latency_ms: u64,
+    ok: bool,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    samples: VecDeque<Sample>,
+    successes: u64,
+    errors: u64,
+    timeouts: u64,
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    cooldown_count: u32,
+    last_error: Option<String>,
+}
+
+/// Rolling latency and error statistics for a single endpoint.
+#[derive(Debug, Default)]
+pub struct EndpointHealth {
+    state: Mutex<HealthState>,
+}
+
+impl EndpointHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_success(&self, latency: Duration, config: &HealthConfig) {
+        let mut state = self.state.lock().unwrap();
+        state.push_sample(Sample { latency_ms: latency.as_millis() as u64, ok: true }, config.window);
+        state.successes += 1;
+        state.consecutive_failures = 0;
+        state.cooldown_until = None;
+        state.cooldown_count = 0;
+    }
+
+    pub fn record_error(&self, latency: Duration, error: &str, config: &HealthConfig) {
+        let mut state = self.state.lock().unwrap();
+        state.push_sample(Sample { latency_ms: latency.as_millis() as u64, ok: false }, config.window);
+        state.errors += 1;
+        state.last_error = Some(error.to_string());
+        state.register_failure(config);
+    }
+
+    pub fn record_timeout(&self, timeout: Duration, config: &HealthConfig) {
+        let mut state = self.state.lock().unwrap();
+        state.push_sample(Sample { latency_ms: timeout.as_millis() as u64, ok: false }, config.window);
+        state.timeouts += 1;
+        state.last_error = Some(format!("timed out after {:?}", timeout));
+        state.register_failure(config);
+    }
+
+    pub fn status(&self) -> EndpointStatus {
+        self.state.lock().unwrap().status(Instant::now())
+    }
+
+    /// Lower is better: median latency inflated by the recent error rate.
+    pub fn score(&self, config: &HealthConfig) -> f64 {
+        self.state.lock().unwrap().score(config)
+    }
+
+    /// Latency at the given percentile (0-100) over the recent window
+    pub fn latency_percentile(&self, percentile: u8) -> Option<Duration> {
+        self.state.lock().unwrap()
+            .percentile(percentile)
+            .map(Duration::from_millis)
+    }
+
+    pub fn snapshot(&self, url: &str, domain: &str, config: &HealthConfig) -> EndpointHealthSnapshot {
+        let state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        EndpointHealthSnapshot {
+            url: url.to_string(),
+            domain: domain.to_string(),
+            status: state.status(now),
+            score: state.score(config),
+            p50_ms: state.percentile(50),
+            p90_ms: state.percentile(90),
+            p99_ms: state.percentile(99),
+            error_rate: state.error_rate(),
+            successes: state.successes,
+            errors: state.errors,
+            timeouts: state.timeouts,
+            consecutive_failures: state.consecutive_failures,
+            cooldown_remaining_ms: state.cooldown_until
+                .filter(|until| *until > now)
+                .map(|until| (until - now).as_millis() as u64),
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+impl HealthState {
+    fn push_sample(&mut self, sample: Sample, window: usize) {
+        self.samples.push_back(sample);
+        while self.samples.len() > window.max(1) {
+            self.samples.pop_front();
+        }
+    }
+
+    fn register_failure(&mut self, config: &HealthConfig) {
+        self.consecutive_failures += 1;
+
+        if self.consecutive_failures >= config.failure_threshold {
+            // A failed probe (or a fresh run of failures) restarts the cool-down,
+            // doubling it each time up to the configured maximum
+            let factor = 2u32.saturating_pow(self.cooldown_count.min(16));
+            let cooldown = config.cooldown.saturating_mul(factor).min(config.max_cooldown);
+            self.cooldown_until = Some(Instant::now() + cooldown);
+            self.cooldown_count += 1;
+        }
+    }
+
+    fn status(&self, now: Instant) -> EndpointStatus {
+        match self.cooldown_until {
+            Some(until) if until > now => EndpointStatus::CoolingDown,
+            Some(_) => EndpointStatus::Probing,
+            None => EndpointStatus::Healthy,
+        }
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let failures = self.samples.iter().filter(|sample| !sample.ok).count();
+        failures as f64 / self.samples.len() as f64
+    }
+
+    fn percentile(&self, percentile: u8) -> Option<u64> {
+        let mut latencies: Vec<u64> = self.samples.iter()
+            .filter(|sample| sample.ok)
+            .map(|sample| sample.latency_ms)
+            .collect();
+
+        if latencies.is_empty() {
+            return None;
+        }
+
+        latencies.sort_unstable();
+        let index = (latencies.len() - 1) * percentile.min(100) as usize / 100;
+        Some(latencies[index])
+    }
+
+    fn score(&self, config: &HealthConfig) -> f64 {
+        match self.percentile(50) {
+            Some(p50) => p50 as f64 * (1.0 + config.error_penalty * self.error_rate()),
+            // Unmeasured endpoints go first so they get measured early
+            None if self.samples.is_empty() => 0.0,
+            None => f64::MAX,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> HealthConfig {
+        HealthConfig { cooldown: Duration::from_millis(50), max_cooldown: Duration::from_millis(120), ..HealthConfig::default() }
+    }
+
+    fn fail(health: &EndpointHealth, times: u32, config: &HealthConfig) {
+        for _ in 0..times {
+            health.record_error(Duration::from_millis(10), "connection refused", config);
+        }
+    }
+
+    #[test]
+    fn cools_down_after_consecutive_failures() {
+        let config = config();
+        let health = EndpointHealth::new();
+
+        fail(&health, config.failure_threshold - 1, &config);
+        assert_eq!(health.status(), EndpointStatus::Healthy);
+
+        // A success in between starts the count again
+        health.record_success(Duration::from_millis(10), &config);
+        fail(&health, config.failure_threshold - 1, &config);
+        assert_eq!(health.status(), EndpointStatus::Healthy);
+
+        fail(&health, 1, &config);
+        assert_eq!(health.status(), EndpointStatus::CoolingDown);
+        assert_eq!(health.snapshot("url", "domain", &config).last_error.as_deref(), Some("connection refused"));
+    }
+
+    #[test]
+    fn probes_once_the_cooldown_elapses_and_recovers_on_success() {
+        let config = config();
+        let health = EndpointHealth::new();
+        fail(&health, config.failure_threshold, &config);
+
+        std::thread::sleep(config.cooldown);
+        assert_eq!(health.status(), EndpointStatus::Probing);
+
+        health.record_success(Duration::from_millis(10), &config);
+        assert_eq!(health.status(), EndpointStatus::Healthy);
+        assert_eq!(health.snapshot("url", "domain", &config).cooldown_remaining_ms, None);
+    }
+
+    #[test]
+    fn failed_probes_double_the_cooldown_up_to_the_maximum() {
+        let config = config();
+        let health = EndpointHealth::new();
+        let cooldown_ms = |health: &EndpointHealth| health.snapshot("url", "domain", &config).cooldown_remaining_ms.unwrap();
+
+        fail(&health, config.failure_threshold, &config);
+        assert!(cooldown_ms(&health) <= 50);
+
+        std::thread::sleep(config.cooldown);
+        fail(&health, 1, &config);
+        assert!(cooldown_ms(&health) > 50 && cooldown_ms(&health) <= 100);
+
+        fail(&health, 1, &config);
+        assert!(cooldown_ms(&health) > 100 && cooldown_ms(&health) <= 120);
+    }
+
+    #[test]
+    fn timeouts_count_as_failures() {
+        let config = config();
+        let health = EndpointHealth::new();
+
+        for _ in 0..config.failure_threshold {
+            health.record_timeout(Duration::from_secs(1), &config);
+        }
+
+        let snapshot = health.snapshot("url", "domain", &config);
+        assert_eq!(snapshot.status, EndpointStatus::CoolingDown);
+        assert_eq!(snapshot.timeouts, u64::from(config.failure_threshold));
+    }
+
+    #[test]
+    fn scores_median_latency_inflated_by_the_error_rate() {
+        let config = config();
+        let health = EndpointHealth::new();
+        assert_eq!(health.score(&config), 0.0);
+
+        for latency in [100, 200, 300] {
+            health.record_success(Duration::from_millis(latency), &config);
+        }
+        assert_eq!(health.score(&config), 200.0);
+
+        // One failure in four: 200ms * (1 + 4.0 * 0.25)
+        health.record_error(Duration::from_millis(5), "error", &config);
+        assert_eq!(health.score(&config), 400.0);
+    }
+
+    #[test]
+    fn scores_only_failures_last() {
+        let config = config();
+        let health = EndpointHealth::new();
+        health.record_error(Duration::from_millis(5), "error", &config);
+
+        assert_eq!(health.score(&config), f64::MAX);
+    }
+
+    #[test]
+    fn keeps_only_the_recent_window() {
+        let config = HealthConfig { window: 2, ..config() };
+        let health = EndpointHealth::new();
+
+        health.record_error(Duration::from_millis(5), "error", &config);
+        health.record_success(Duration::from_millis(100), &config);
+        health.record_success(Duration::from_millis(100), &config);
+
+        assert_eq!(health.score(&config), 100.0);
+        assert_eq!(health.latency_percentile(50), Some(Duration::from_millis(100)));
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+
+use crate::rpc::{
+    client::{CnctdSolana, TransactionExt},
+    error::{CnctdSolanaError, Result},
+    pool::RpcCapability,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityLevel {
+    Min,
+    Low,
+    Medium,
+    High,
+    VeryHigh,
+    UnsafeMax,
+    Default,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityFeeOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority_level: Option<PriorityLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_all_priority_fee_levels: Option<bool>,
+    /// Helius' recommended fee, never below its staked-connection minimum
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recommended: Option<bool>,
+}
+
+/// Fees in micro-lamports per compute unit
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityFeeLevels {
+    pub min: f64,
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+    pub very_high: f64,
+    pub unsafe_max: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityFeeEstimate {
+    /// Micro-lamports per compute unit
+    pub priority_fee_estimate: Option<f64>,
+    pub priority_fee_levels: Option<PriorityFeeLevels>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AssetMetadata {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub symbol: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AssetLinks {
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub external_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AssetContent {
+    #[serde(default)]
+    pub json_uri: String,
+    #[serde(default)]
+    pub metadata: AssetMetadata,
+    #[serde(default)]
+    pub links: Option<AssetLinks>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssetOwnership {
+    pub owner: String,
+    #[serde(default)]
+    pub delegate: Option<String>,
+    #[serde(default)]
+    pub delegated: bool,
+    #[serde(default)]
+    pub frozen: bool,
+    #[serde(default)]
+    pub ownership_model: String,
+}
+
+/// A DAS asset. The fields wallet displays need are typed; everything else
+/// the provider returns is kept in `other`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Asset {
+    pub id: String,
+    pub interface: String,
+    #[serde(default)]
+    pub content: Option<AssetContent>,
+    #[serde(default)]
+    pub ownership: Option<AssetOwnership>,
+    #[serde(default)]
+    pub mutable: bool,
+    #[serde(default)]
+    pub burnt: bool,
+    #[serde(default)]
+    pub token_info: Option<Value>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssetList {
+    pub total: u32,
+    pub limit: u32,
+    #[serde(default)]
+    pub page: Option<u32>,
+    pub items: Vec<Asset>,
+}
+
+/// `searchAssets` filters; unset fields are left out of the request
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchAssets {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority_address: Option<String>,
+    /// `["collection", <collection address>]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grouping: Option<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burnt: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<bool>,
+    /// `fungible`, `nonFungible`, `regularNft`, `compressedNft` or `all`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl CnctdSolana {
+    /// Whether any endpoint in the pool serves `capability`
+    pub fn supports(&self, capability: RpcCapability) -> bool {
+        self.pool.endpoints().iter().any(|endpoint| endpoint.supports(capability))
+    }
+
+    /// Helius fee estimate for `transaction`, in micro-lamports per compute unit
+    pub async fn get_priority_fee_estimate(&self, transaction: &Transaction, options: PriorityFeeOptions) -> Result<PriorityFeeEstimate> {
+        let mut options = serde_json::to_value(options).map_err(|e| CnctdSolanaError::Serialization(e.to_string()))?;
+        options["transactionEncoding"] = json!("Base64");
+
+        let params = json!([{ "transaction": transaction.to_base64_string()?, "options": options }]);
+        self.call_provider_method(RpcCapability::PriorityFeeEstimate, "getPriorityFeeEstimate", params).await
+    }
+
+    /// Helius fee estimate for transactions writing to `account_keys`
+    pub async fn get_priority_fee_estimate_for_accounts(&self, account_keys: &[Pubkey], options: PriorityFeeOptions) -> Result<PriorityFeeEstimate> {
+        let account_keys: Vec<String> = account_keys.iter().map(Pubkey::to_string).collect();
+
+        let params = json!([{ "accountKeys": account_keys, "options": options }]);
+        self.call_provider_method(RpcCapability::PriorityFeeEstimate, "getPriorityFeeEstimate", params).await
+    }
+
+    pub async fn get_asset(&self, id: &Pubkey) -> Result<Asset> {
+        let params = json!({ "id": id.to_string() });
+        self.call_provider_method(RpcCapability::DigitalAssetStandard, "getAsset", params).await
+    }
+
+    /// One page (starting at 1) of the assets owned by `owner`
+    pub async fn get_assets_by_owner(&self, owner: &Pubkey, page: u32, limit: u32) -> Result<AssetList> {
+        let params = json!({ "ownerAddress": owner.to_string(), "page": page, "limit": limit });
+        self.call_provider_method(RpcCapability::DigitalAssetStandard, "getAssetsByOwner", params).await
+    }
+
+    pub async fn search_assets(&self, search: &SearchAssets) -> Result<AssetList> {
+        let params = serde_json::to_value(search).map_err(|e| CnctdSolanaError::Serialization(e.to_string()))?;
+        self.call_provider_method(RpcCapability::DigitalAssetStandard, "searchAssets", params).await
+    }
+
+    /// Sends `method` to the endpoints that serve `capability`
+    async fn call_provider_method<T: DeserializeOwned>(&self, capability: RpcCapability, method: &'static str, params: Value) -> Result<T> {
+        let pool = self.pool.filter(|endpoint| endpoint.supports(capability));
+        if pool.endpoints().is_empty() {
+            return Err(CnctdSolanaError::Config(format!("No RPC endpoint in the pool supports {:?}", capability)));
+        }
+
+        let result = pool.call(method, |transport| {
+            let params = params.clone();
+            async move { transport.send_json_request(method, params).await }
+        }).await?;
+
+        serde_json::from_value(result).map_err(|e| CnctdSolanaError::Deserialization(format!("{} response: {}", method, e)))
+    }
+}
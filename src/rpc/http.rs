@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use serde::{Deserialize, Serialize};
+use solana_rpc_client::http_sender::HttpSender;
+
+use crate::rpc::rate_limit::RetryAfterMiddleware;
+
+/// Settings for the HTTP client an `RpcPool` shares across its endpoints.
+///
+/// One client holds one keep-alive connection pool, so requests reuse open
+/// (and, for HTTPS, already negotiated) connections instead of paying for a
+/// new one per call.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct HttpConfig {
+    /// Whole-request timeout, matching `HttpSender::new` by default
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// How long an unused connection is kept open
+    pub pool_idle_timeout: Duration,
+    /// Most idle connections kept open to each host for reuse. Not a cap on
+    /// open connections: a burst of concurrent calls opens as many as it
+    /// needs, and those beyond this are closed once idle.
+    pub pool_max_idle_per_host: usize,
+    /// Speak HTTP/2 without negotiating it first. Over HTTPS, HTTP/2 is
+    /// already used whenever the provider offers it; only set this for
+    /// endpoints known to support it, as HTTP/1-only servers will fail.
+    pub http2_prior_knowledge: bool,
+    /// Interval for HTTP/2 pings that keep idle connections alive
+    pub http2_keep_alive_interval: Option<Duration>,
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 32,
+            http2_prior_knowledge: false,
+            http2_keep_alive_interval: Some(Duration::from_secs(30)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn with_pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = pool_idle_timeout;
+        self
+    }
+
+    pub fn with_pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool_max_idle_per_host = pool_max_idle_per_host;
+        self
+    }
+
+    pub fn with_http2_prior_knowledge(mut self, http2_prior_knowledge: bool) -> Self {
+        self.http2_prior_knowledge = http2_prior_knowledge;
+        self
+    }
+
+    /// Builds the client, with 429s surfaced as errors so the pool can back
+    /// off and fail over rather than `HttpSender` retrying internally
+    pub fn build(&self) -> ClientWithMiddleware {
+        let mut builder = reqwest::Client::builder()
+            .default_headers(HttpSender::default_headers())
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .http2_keep_alive_interval(self.http2_keep_alive_interval)
+            .http2_keep_alive_while_idle(self.http2_keep_alive_interval.is_some())
+            .tcp_keepalive(self.tcp_keepalive);
+
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+
+        ClientBuilder::new(builder.build().expect("build rpc client"))
+            .with(RetryAfterMiddleware)
+            .build()
+    }
+}
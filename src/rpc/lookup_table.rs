@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use solana_address_lookup_table_interface::{
+    instruction as lookup_table_instruction,
+    state::{AddressLookupTable, LOOKUP_TABLE_MAX_ADDRESSES},
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    account::Account, instruction::Instruction, message::AddressLookupTableAccount, pubkey::Pubkey,
+    signature::Signature, signer::Signer, transaction::Transaction,
+};
+
+use crate::rpc::{
+    client::CnctdSolana,
+    error::{CnctdSolanaError, Result},
+};
+
+/// How long a fetched lookup table is served from the cache
+pub const DEFAULT_LOOKUP_TABLE_TTL: Duration = Duration::from_secs(60);
+
+/// Addresses added per extend transaction, keeping it well inside the
+/// transaction size limit
+pub const LOOKUP_TABLE_EXTEND_CHUNK_SIZE: usize = 20;
+
+/// A decoded address lookup table account
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookupTable {
+    pub address: Pubkey,
+    /// `None` once the table is frozen
+    pub authority: Option<Pubkey>,
+    /// `None` while the table is active
+    pub deactivation_slot: Option<u64>,
+    /// Addresses added in this slot cannot be looked up until the next one
+    pub last_extended_slot: u64,
+    pub addresses: Vec<Pubkey>,
+}
+
+impl LookupTable {
+    pub fn decode(address: Pubkey, account: &Account) -> Result<Self> {
+        let table = AddressLookupTable::deserialize(&account.data)
+            .map_err(|e| CnctdSolanaError::Deserialization(format!("lookup table {}: {}", address, e)))?;
+
+        Ok(Self {
+            address,
+            authority: table.meta.authority,
+            deactivation_slot: Some(table.meta.deactivation_slot).filter(|slot| *slot != u64::MAX),
+            last_extended_slot: table.meta.last_extended_slot,
+            addresses: table.addresses.to_vec(),
+        })
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.authority.is_none()
+    }
+
+    /// The form `compile_versioned_transaction` takes
+    pub fn account(&self) -> AddressLookupTableAccount {
+        AddressLookupTableAccount {
+            key: self.address,
+            addresses: self.addresses.clone(),
+        }
+    }
+}
+
+/// Lookup tables fetched recently. Tables only change through extension,
+/// freezing and deactivation, and this client's own changes invalidate
+/// the entry, so a short TTL is enough to pick up anyone else's.
+#[derive(Debug)]
+pub struct LookupTableCache {
+    ttl: Duration,
+    tables: Mutex<HashMap<Pubkey, (LookupTable, Instant)>>,
+}
+
+impl Default for LookupTableCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOOKUP_TABLE_TTL)
+    }
+}
+
+impl LookupTableCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            tables: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn get(&self, address: &Pubkey) -> Option<LookupTable> {
+        self.tables.lock().unwrap()
+            .get(address)
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < self.ttl)
+            .map(|(table, _)| table.clone())
+    }
+
+    pub fn insert(&self, table: LookupTable) {
+        self.tables.lock().unwrap().insert(table.address, (table, Instant::now()));
+    }
+
+    pub fn invalidate(&self, address: &Pubkey) {
+        self.tables.lock().unwrap().remove(address);
+    }
+
+    pub fn clear(&self) {
+        self.tables.lock().unwrap().clear();
+    }
+}
+
+impl CnctdSolana {
+    pub fn with_lookup_table_ttl(mut self, ttl: Duration) -> Self {
+        self.lookup_table_cache = LookupTableCache::new(ttl);
+        self
+    }
+
+    /// Fetches and decodes a lookup table, from the cache when fresh
+    pub async fn get_lookup_table(&self, address: &Pubkey) -> Result<LookupTable> {
+        let mut tables = self.get_lookup_tables(&[*address]).await?;
+
+        Ok(tables.remove(0))
+    }
+
+    /// Lookup tables in the order given, fetching only those not cached
+    pub async fn get_lookup_tables(&self, addresses: &[Pubkey]) -> Result<Vec<LookupTable>> {
+        let cached: Vec<Option<LookupTable>> = addresses.iter().map(|address| self.lookup_table_cache.get(address)).collect();
+        let missing: Vec<Pubkey> = addresses
+            .iter()
+            .zip(&cached)
+            .filter(|(_, table)| table.is_none())
+            .map(|(address, _)| *address)
+            .collect();
+
+        let mut fetched = HashMap::new();
+        if !missing.is_empty() {
+            let accounts = self.get_multiple_accounts(&missing).await?;
+
+            for (address, account) in missing.iter().zip(accounts) {
+                let account = account.ok_or(CnctdSolanaError::AccountNotFound(*address))?;
+                let table = LookupTable::decode(*address, &account)?;
+                self.lookup_table_cache.insert(table.clone());
+                fetched.insert(*address, table);
+            }
+        }
+
+        Ok(addresses
+            .iter()
+            .zip(cached)
+            .map(|(address, table)| table.unwrap_or_else(|| fetched[address].clone()))
+            .collect())
+    }
+
+    /// Lookup tables in the form `compile_versioned_transaction` takes
+    pub async fn get_address_lookup_tables(&self, addresses: &[Pubkey]) -> Result<Vec<AddressLookupTableAccount>> {
+        let tables = self.get_lookup_tables(addresses).await?;
+
+        Ok(tables.iter().map(LookupTable::account).collect())
+    }
+
+    /// Creates a lookup table owned by the instance signer, which also pays
+    /// for it, and returns its address once confirmed
+    pub async fn create_lookup_table(&self) -> Result<Pubkey> {
+        let authority = self.lookup_table_authority()?;
+        // The program derives the address from a slot it can find in SlotHashes
+        let recent_slot = self.get_slot_with_commitment(CommitmentConfig::finalized()).await?;
+
+        let (instruction, address) = lookup_table_instruction::create_lookup_table(authority, authority, recent_slot);
+        self.send_lookup_table_instruction(instruction).await?;
+
+        Ok(address)
+    }
+
+    /// Appends whichever of `addresses` the table does not hold yet, in
+    /// transactions of `LOOKUP_TABLE_EXTEND_CHUNK_SIZE` addresses. New
+    /// addresses can be looked up from the slot after they were added.
+    pub async fn extend_lookup_table(&self, table: Pubkey, addresses: &[Pubkey]) -> Result<Vec<Signature>> {
+        let authority = self.lookup_table_authority()?;
+
+        self.lookup_table_cache.invalidate(&table);
+        let current = self.get_lookup_table(&table).await?;
+
+        let mut new_addresses: Vec<Pubkey> = Vec::new();
+        for address in addresses {
+            if !current.addresses.contains(address) && !new_addresses.contains(address) {
+                new_addresses.push(*address);
+            }
+        }
+
+        if current.addresses.len() + new_addresses.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+            return Err(CnctdSolanaError::InvalidInput(format!(
+                "lookup table {} would hold {} addresses, more than the {} allowed",
+                table,
+                current.addresses.len() + new_addresses.len(),
+                LOOKUP_TABLE_MAX_ADDRESSES
+            )));
+        }
+
+        let mut signatures = Vec::new();
+        for chunk in new_addresses.chunks(LOOKUP_TABLE_EXTEND_CHUNK_SIZE) {
+            let instruction = lookup_table_instruction::extend_lookup_table(table, authority, Some(authority), chunk.to_vec());
+            let signature = self.send_lookup_table_instruction(instruction).await;
+            self.lookup_table_cache.invalidate(&table);
+            signatures.push(signature?);
+        }
+
+        Ok(signatures)
+    }
+
+    /// Makes the table permanent: it can no longer be extended, deactivated
+    /// or closed
+    pub async fn freeze_lookup_table(&self, table: Pubkey) -> Result<Signature> {
+        let authority = self.lookup_table_authority()?;
+        let instruction = lookup_table_instruction::freeze_lookup_table(table, authority);
+
+        self.send_lookup_table_change(table, instruction).await
+    }
+
+    /// Starts the cool-down after which the table can be closed. Versioned
+    /// transactions using it fail once it is deactivated.
+    pub async fn deactivate_lookup_table(&self, table: Pubkey) -> Result<Signature> {
+        let authority = self.lookup_table_authority()?;
+        let instruction = lookup_table_instruction::deactivate_lookup_table(table, authority);
+
+        self.send_lookup_table_change(table, instruction).await
+    }
+
+    /// Closes a deactivated table and sends its rent to `recipient`. Fails
+    /// until the deactivation slot has left the SlotHashes sysvar, roughly
+    /// 513 slots after deactivating.
+    pub async fn close_lookup_table(&self, table: Pubkey, recipient: Pubkey) -> Result<Signature> {
+        let authority = self.lookup_table_authority()?;
+        let instruction = lookup_table_instruction::close_lookup_table(table, authority, recipient);
+
+        self.send_lookup_table_change(table, instruction).await
+    }
+
+    fn lookup_table_authority(&self) -> Result<Pubkey> {
+        self.signer_keypair
+            .as_ref()
+            .map(|keypair| keypair.pubkey())
+            .ok_or(CnctdSolanaError::MissingSigner)
+    }
+
+    async fn send_lookup_table_change(&self, table: Pubkey, instruction: Instruction) -> Result<Signature> {
+        let result = self.send_lookup_table_instruction(instruction).await;
+        self.lookup_table_cache.invalidate(&table);
+
+        result
+    }
+
+    async fn send_lookup_table_instruction(&self, instruction: Instruction) -> Result<Signature> {
+        let payer = self.lookup_table_authority()?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer));
+
+        self.sign_and_confirm_transaction(&transaction, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::rpc::MemoryTransport;
+
+    /// A lookup table account's data as the program lays it out: the
+    /// bincode `ProgramState::LookupTable` meta padded to 56 bytes, then
+    /// the addresses
+    fn table_data(deactivation_slot: u64, authority: Option<Pubkey>, addresses: &[Pubkey]) -> Vec<u8> {
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend(deactivation_slot.to_le_bytes());
+        data.extend(42u64.to_le_bytes()); // last_extended_slot
+        data.push(0); // last_extended_slot_start_index
+        match authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend(authority.to_bytes());
+            }
+            None => data.push(0),
+        }
+        data.resize(56, 0);
+        addresses.iter().for_each(|address| data.extend(address.to_bytes()));
+
+        data
+    }
+
+    fn table_account(data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: solana_address_lookup_table_interface::program::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn decodes_active_and_frozen_tables() {
+        let address = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let addresses = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let active = LookupTable::decode(address, &table_account(table_data(u64::MAX, Some(authority), &addresses))).unwrap();
+        assert_eq!(active, LookupTable {
+            address,
+            authority: Some(authority),
+            deactivation_slot: None,
+            last_extended_slot: 42,
+            addresses: addresses.to_vec(),
+        });
+        assert!(!active.is_frozen());
+
+        let frozen = LookupTable::decode(address, &table_account(table_data(100, None, &addresses))).unwrap();
+        assert!(frozen.is_frozen());
+        assert_eq!(frozen.deactivation_slot, Some(100));
+        assert_eq!(frozen.account().addresses, addresses.to_vec());
+    }
+
+    #[test]
+    fn rejects_accounts_that_are_not_tables() {
+        let result = LookupTable::decode(Pubkey::new_unique(), &table_account(vec![0; 10]));
+
+        assert!(matches!(result, Err(CnctdSolanaError::Deserialization(_))));
+    }
+
+    #[tokio::test]
+    async fn get_lookup_tables_keeps_order_and_serves_cached_tables() {
+        let memory = Arc::new(MemoryTransport::new());
+        let tables: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for table in &tables {
+            memory.set_account(*table, table_account(table_data(u64::MAX, None, &[*table])));
+        }
+        let client = CnctdSolana::with_transport(memory.clone()).unwrap();
+
+        client.get_lookup_table(&tables[1]).await.unwrap();
+        // Only the cache still has it
+        memory.remove_account(&tables[1]);
+
+        let fetched = client.get_lookup_tables(&[tables[2], tables[1], tables[0]]).await.unwrap();
+        let order: Vec<Pubkey> = fetched.iter().map(|table| table.address).collect();
+        assert_eq!(order, vec![tables[2], tables[1], tables[0]]);
+        assert!(fetched.iter().all(|table| table.addresses == vec![table.address]));
+
+        client.lookup_table_cache.invalidate(&tables[1]);
+        let missing = client.get_lookup_tables(&[tables[0], tables[1]]).await;
+        assert!(matches!(missing, Err(CnctdSolanaError::AccountNotFound(address)) if address == tables[1]));
+    }
+}
@@ -0,0 +1,646 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_custom_error::{JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE},
+    rpc_filter::RpcFilterType,
+    rpc_request::{RpcError, RpcResponseErrorData},
+    rpc_response::{Response, RpcPrioritizationFee, RpcResponseContext, RpcSimulateTransactionResult},
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    account::Account, hash::Hash, pubkey::Pubkey, rent::Rent, signature::Signature,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+
+use crate::rpc::transport::RpcTransport;
+
+/// JSON-RPC error code for an unknown method
+const JSON_RPC_METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for invalid params
+const JSON_RPC_INVALID_PARAMS: i64 = -32602;
+
+/// Most keys a node accepts in one `getMultipleAccounts` request
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Number of blocks a blockhash stays valid for, matching mainnet
+const BLOCKHASH_VALID_BLOCKS: u64 = 150;
+
+struct MemoryState {
+    accounts: HashMap<Pubkey, Account>,
+    slot: u64,
+    block_height: u64,
+    blockhash: Hash,
+    units_consumed: u64,
+    simulation_error: Option<TransactionError>,
+    prioritization_fees: Vec<u64>,
+    sent_transactions: Vec<VersionedTransaction>,
+    simulated_transactions: Vec<VersionedTransaction>,
+    statuses: HashMap<Signature, TransactionStatus>,
+    dropped_sends: usize,
+    failing_status_polls: usize,
+    json_responses: HashMap<String, Value>,
+    json_requests: Vec<(String, Value)>,
+}
+
+/// In-process `RpcTransport` that stores accounts in memory and records every
+/// transaction sent through it, legacy or versioned. Sent transactions are reported as finalized
+/// at the current slot, unless dropped with `drop_sends`; account state is not modified by them.
+pub struct MemoryTransport {
+    state: RwLock<MemoryState>,
+}
+
+impl Default for MemoryTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(MemoryState {
+                accounts: HashMap::new(),
+                slot: 1,
+                block_height: 1,
+                blockhash: Hash::new_unique(),
+                units_consumed: 0,
+                simulation_error: None,
+                prioritization_fees: Vec::new(),
+                sent_transactions: Vec::new(),
+                simulated_transactions: Vec::new(),
+                statuses: HashMap::new(),
+                dropped_sends: 0,
+                failing_status_polls: 0,
+                json_responses: HashMap::new(),
+                json_requests: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.state.write().unwrap().accounts.insert(pubkey, account);
+    }
+
+    pub fn remove_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.state.write().unwrap().accounts.remove(pubkey)
+    }
+
+    pub fn account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.state.read().unwrap().accounts.get(pubkey).cloned()
+    }
+
+    /// Advances the slot and block height, rotating the latest blockhash
+    pub fn advance_slot(&self, slots: u64) {
+        let mut state = self.state.write().unwrap();
+        state.slot += slots;
+        state.block_height += slots;
+        state.blockhash = Hash::new_unique();
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.state.read().unwrap().slot
+    }
+
+    pub fn set_units_consumed(&self, units_consumed: u64) {
+        self.state.write().unwrap().units_consumed = units_consumed;
+    }
+
+    /// Makes simulations, and sends that do not skip preflight, fail with `error`
+    pub fn set_simulation_error(&self, error: Option<TransactionError>) {
+        self.state.write().unwrap().simulation_error = error;
+    }
+
+    /// Fees reported by `get_recent_prioritization_fees`, oldest first,
+    /// for the slots leading up to the current one
+    pub fn set_prioritization_fees(&self, fees: Vec<u64>) {
+        self.state.write().unwrap().prioritization_fees = fees;
+    }
+
+    /// The next `count` sends are recorded but never land, as when a node
+    /// drops them under load
+    pub fn drop_sends(&self, count: usize) {
+        self.state.write().unwrap().dropped_sends = count;
+    }
+
+    /// The next `count` `get_signature_statuses` calls fail as if the node
+    /// could not be reached
+    pub fn fail_status_polls(&self, count: usize) {
+        self.state.write().unwrap().failing_status_polls = count;
+    }
+
+    /// Legacy transactions sent so far
+    pub fn sent_transactions(&self) -> Vec<Transaction> {
+        self.state.read().unwrap().sent_transactions
+            .iter()
+            .filter_map(|transaction| transaction.clone().into_legacy_transaction())
+            .collect()
+    }
+
+    /// Every transaction sent so far, with legacy ones as
+    /// `VersionedMessage::Legacy`
+    pub fn sent_versioned_transactions(&self) -> Vec<VersionedTransaction> {
+        self.state.read().unwrap().sent_transactions.clone()
+    }
+
+    /// Every transaction simulated so far, with legacy ones as
+    /// `VersionedMessage::Legacy`
+    pub fn simulated_versioned_transactions(&self) -> Vec<VersionedTransaction> {
+        self.state.read().unwrap().simulated_transactions.clone()
+    }
+
+    /// Stands in for a provider-specific method: `send_json_request` calls
+    /// to `method` return `result`
+    pub fn set_json_response(&self, method: &str, result: Value) {
+        self.state.write().unwrap().json_responses.insert(method.to_string(), result);
+    }
+
+    /// Every `send_json_request` call as `(method, params)`
+    pub fn json_requests(&self) -> Vec<(String, Value)> {
+        self.state.read().unwrap().json_requests.clone()
+    }
+
+    fn simulation_result(state: &MemoryState) -> RpcSimulateTransactionResult {
+        RpcSimulateTransactionResult {
+            err: state.simulation_error.clone().map(Into::into),
+            logs: Some(Vec::new()),
+            accounts: None,
+            units_consumed: Some(state.units_consumed),
+            loaded_accounts_data_size: None,
+            return_data: None,
+            inner_instructions: None,
+            replacement_blockhash: None,
+            fee: None,
+            pre_balances: None,
+            post_balances: None,
+            pre_token_balances: None,
+            post_token_balances: None,
+            loaded_addresses: None,
+        }
+    }
+
+    fn simulate(&self, transaction: VersionedTransaction, config: RpcSimulateTransactionConfig) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        let mut state = self.state.write().unwrap();
+        check_min_context_slot(&state, config.min_context_slot)?;
+        state.simulated_transactions.push(transaction);
+
+        Ok(Response {
+            context: context(state.slot),
+            value: Self::simulation_result(&state),
+        })
+    }
+
+    fn send(&self, transaction: VersionedTransaction, config: RpcSendTransactionConfig) -> ClientResult<Signature> {
+        let mut state = self.state.write().unwrap();
+        check_min_context_slot(&state, config.min_context_slot)?;
+
+        if !config.skip_preflight && state.simulation_error.is_some() {
+            return Err(RpcError::RpcResponseError {
+                code: JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+                message: "Transaction simulation failed".to_string(),
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(Self::simulation_result(&state)),
+            }.into());
+        }
+
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        let status = TransactionStatus {
+            slot: state.slot,
+            confirmations: None,
+            status: Ok(()),
+            err: None,
+            confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+        };
+
+        if state.dropped_sends > 0 {
+            state.dropped_sends -= 1;
+        } else {
+            state.statuses.insert(signature, status);
+        }
+        state.sent_transactions.push(transaction);
+
+        Ok(signature)
+    }
+}
+
+fn context(slot: u64) -> RpcResponseContext {
+    RpcResponseContext { slot, api_version: None }
+}
+
+/// Fails like a node that is behind `min_context_slot`
+fn check_min_context_slot(state: &MemoryState, min_context_slot: Option<u64>) -> ClientResult<()> {
+    match min_context_slot {
+        Some(min_context_slot) if min_context_slot > state.slot => Err(RpcError::RpcResponseError {
+            code: JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+            message: "Minimum context slot has not been reached".to_string(),
+            data: RpcResponseErrorData::Empty,
+        }.into()),
+        _ => Ok(()),
+    }
+}
+
+fn filter_matches(filter: &RpcFilterType, account: &Account) -> bool {
+    match filter {
+        RpcFilterType::DataSize(size) => account.data.len() as u64 == *size,
+        RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&account.data),
+        RpcFilterType::TokenAccountState => true,
+    }
+}
+
+#[async_trait]
+impl RpcTransport for MemoryTransport {
+    fn url(&self) -> String {
+        "memory://".to_string()
+    }
+
+    async fn get_account_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Option<Account>>> {
+        let state = self.state.read().unwrap();
+        check_min_context_slot(&state, config.min_context_slot)?;
+
+        Ok(Response {
+            context: context(state.slot),
+            value: state.accounts.get(pubkey).cloned(),
+        })
+    }
+
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Vec<Option<Account>>>> {
+        if pubkeys.len() > MAX_MULTIPLE_ACCOUNTS {
+            return Err(RpcError::RpcResponseError {
+                code: JSON_RPC_INVALID_PARAMS,
+                message: format!("Too many inputs provided; max {}", MAX_MULTIPLE_ACCOUNTS),
+                data: RpcResponseErrorData::Empty,
+            }.into());
+        }
+
+        let state = self.state.read().unwrap();
+        check_min_context_slot(&state, config.min_context_slot)?;
+
+        Ok(Response {
+            context: context(state.slot),
+            value: pubkeys.iter().map(|pubkey| state.accounts.get(pubkey).cloned()).collect(),
+        })
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let state = self.state.read().unwrap();
+        check_min_context_slot(&state, config.account_config.min_context_slot)?;
+        let filters = config.filters.unwrap_or_default();
+
+        Ok(state.accounts
+            .iter()
+            .filter(|(_, account)| account.owner == *program_id)
+            .filter(|(_, account)| filters.iter().all(|filter| filter_matches(filter, account)))
+            .map(|(pubkey, account)| (*pubkey, account.clone()))
+            .collect())
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        _commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        let state = self.state.read().unwrap();
+
+        Ok((state.blockhash, state.block_height + BLOCKHASH_VALID_BLOCKS))
+    }
+
+    async fn get_slot_with_commitment(&self, _commitment: CommitmentConfig) -> ClientResult<u64> {
+        Ok(self.state.read().unwrap().slot)
+    }
+
+    async fn get_block_height_with_commitment(&self, _commitment: CommitmentConfig) -> ClientResult<u64> {
+        Ok(self.state.read().unwrap().block_height)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        Ok(Rent::default().minimum_balance(data_len))
+    }
+
+    async fn get_recent_prioritization_fees(&self, _addresses: &[Pubkey]) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        let state = self.state.read().unwrap();
+        let first_slot = (state.slot + 1).saturating_sub(state.prioritization_fees.len() as u64);
+
+        Ok(state.prioritization_fees
+            .iter()
+            .enumerate()
+            .map(|(index, fee)| RpcPrioritizationFee {
+                slot: first_slot + index as u64,
+                prioritization_fee: *fee,
+            })
+            .collect())
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        self.simulate(transaction.clone().into(), config)
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.send(transaction.clone().into(), config)
+    }
+
+    async fn simulate_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        self.simulate(transaction.clone(), config)
+    }
+
+    async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.send(transaction.clone(), config)
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Response<Vec<Option<TransactionStatus>>>> {
+        let mut state = self.state.write().unwrap();
+        if state.failing_status_polls > 0 {
+            state.failing_status_polls -= 1;
+            return Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused").into());
+        }
+
+        Ok(Response {
+            context: context(state.slot),
+            value: signatures.iter().map(|signature| state.statuses.get(signature).cloned()).collect(),
+        })
+    }
+
+    async fn send_json_request(&self, method: &'static str, params: Value) -> ClientResult<Value> {
+        let mut state = self.state.write().unwrap();
+        state.json_requests.push((method.to_string(), params));
+
+        state.json_responses.get(method).cloned().ok_or_else(|| {
+            RpcError::RpcResponseError {
+                code: JSON_RPC_METHOD_NOT_FOUND,
+                message: format!("Method not found: {}", method),
+                data: RpcResponseErrorData::Empty,
+            }.into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Arc};
+
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use serde_json::json;
+    use solana_sdk::{
+        instruction::{AccountMeta, InstructionError},
+        signature::Keypair,
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::{
+        rpc::{client::CnctdSolana, CnctdSolanaError},
+        utils::FilterableAccount,
+    };
+
+    const LISTING_DISCRIMINATOR: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+    struct Listing {
+        seller: Pubkey,
+        price: u64,
+    }
+
+    impl FilterableAccount for Listing {
+        fn discriminator() -> [u8; 8] {
+            LISTING_DISCRIMINATOR
+        }
+
+        fn get_field_offset(field_name: &str) -> Option<usize> {
+            match field_name {
+                "seller" => Some(8),
+                "price" => Some(40),
+                _ => None,
+            }
+        }
+
+        fn serialize_field_value(field_name: &str, value: &Value) -> Option<Vec<u8>> {
+            match field_name {
+                "seller" => Pubkey::from_str(value.as_str()?).ok().map(|pubkey| pubkey.to_bytes().to_vec()),
+                "price" => value.as_u64().map(|price| price.to_le_bytes().to_vec()),
+                _ => None,
+            }
+        }
+    }
+
+    fn listing_account(program_id: Pubkey, discriminator: [u8; 8], listing: &Listing) -> Account {
+        let mut data = discriminator.to_vec();
+        data.extend(borsh::to_vec(listing).unwrap());
+
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn client_with_signer(memory: &Arc<MemoryTransport>) -> (CnctdSolana, Pubkey) {
+        let signer = Keypair::new();
+        let pubkey = signer.pubkey();
+        let mut client = CnctdSolana::with_transport(memory.clone()).unwrap();
+        client.signer_keypair = Some(signer);
+
+        (client, pubkey)
+    }
+
+    #[tokio::test]
+    async fn get_accounts_by_fields_applies_memcmp_filters() {
+        let memory = Arc::new(MemoryTransport::new());
+        let program_id = Pubkey::new_unique();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let alice_cheap = Pubkey::new_unique();
+        let alice_dear = Pubkey::new_unique();
+        let bob_cheap = Pubkey::new_unique();
+        memory.set_account(alice_cheap, listing_account(program_id, LISTING_DISCRIMINATOR, &Listing { seller: alice, price: 10 }));
+        memory.set_account(alice_dear, listing_account(program_id, LISTING_DISCRIMINATOR, &Listing { seller: alice, price: 99 }));
+        memory.set_account(bob_cheap, listing_account(program_id, LISTING_DISCRIMINATOR, &Listing { seller: bob, price: 10 }));
+        // Same layout, but another account type or another program
+        memory.set_account(Pubkey::new_unique(), listing_account(program_id, [9; 8], &Listing { seller: alice, price: 10 }));
+        memory.set_account(Pubkey::new_unique(), listing_account(Pubkey::new_unique(), LISTING_DISCRIMINATOR, &Listing { seller: alice, price: 10 }));
+
+        let client = CnctdSolana::with_transport(memory.clone()).unwrap();
+
+        let mut by_seller: Vec<Pubkey> = client
+            .get_accounts_by_fields::<Listing>(program_id, &[("seller", json!(alice.to_string()))])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+        by_seller.sort();
+        let mut expected = vec![alice_cheap, alice_dear];
+        expected.sort();
+        assert_eq!(by_seller, expected);
+
+        let by_both = client
+            .get_accounts_by_fields::<Listing>(program_id, &[("seller", json!(alice.to_string())), ("price", json!(10))])
+            .await
+            .unwrap();
+        assert_eq!(by_both, vec![(alice_cheap, Listing { seller: alice, price: 10 })]);
+    }
+
+    #[tokio::test]
+    async fn get_accounts_by_fields_rejects_unknown_fields() {
+        let client = CnctdSolana::with_transport(Arc::new(MemoryTransport::new())).unwrap();
+
+        let result = client.get_accounts_by_fields::<Listing>(Pubkey::new_unique(), &[("buyer", json!(1))]).await;
+
+        assert!(matches!(result, Err(CnctdSolanaError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn get_multiple_accounts_stays_aligned_across_batches() {
+        let memory = Arc::new(MemoryTransport::new());
+        let pubkeys: Vec<Pubkey> = (0..250).map(|_| Pubkey::new_unique()).collect();
+        for (index, pubkey) in pubkeys.iter().enumerate().filter(|(index, _)| index % 3 == 0) {
+            let mut data = vec![0; 8];
+            data.extend(borsh::to_vec(&(index as u64)).unwrap());
+            let account = Account { lamports: index as u64 + 1, data, owner: Pubkey::default(), executable: false, rent_epoch: 0 };
+            memory.set_account(*pubkey, account);
+        }
+        // Too short to hold the discriminator
+        memory.set_account(pubkeys[150], Account { lamports: 1, data: vec![1], owner: Pubkey::default(), executable: false, rent_epoch: 0 });
+        let client = CnctdSolana::with_transport(memory.clone()).unwrap();
+
+        let accounts = client.get_multiple_accounts(&pubkeys).await.unwrap();
+        assert_eq!(accounts.len(), pubkeys.len());
+        for (index, account) in accounts.iter().enumerate() {
+            match index {
+                150 => assert_eq!(account.as_ref().unwrap().lamports, 1),
+                index if index % 3 == 0 => assert_eq!(account.as_ref().unwrap().lamports, index as u64 + 1),
+                _ => assert!(account.is_none(), "{index} should be missing"),
+            }
+        }
+
+        let decoded = client.get_multiple_accounts_data::<u64>(&pubkeys).await.unwrap();
+        assert_eq!(decoded.len(), pubkeys.len());
+        assert!(matches!(decoded[150], Some(Err(CnctdSolanaError::Deserialization(_)))));
+        assert_eq!(*decoded[99].as_ref().unwrap().as_ref().unwrap(), 99);
+        assert_eq!(*decoded[201].as_ref().unwrap().as_ref().unwrap(), 201);
+        assert!(decoded[200].is_none());
+    }
+
+    #[tokio::test]
+    async fn sign_and_confirm_transaction_signs_with_latest_blockhash() {
+        let memory = Arc::new(MemoryTransport::new());
+        let (client, payer) = client_with_signer(&memory);
+        let transaction = client
+            .create_unsigned_transaction(Pubkey::new_unique(), "list", 10u64, vec![AccountMeta::new(payer, true)])
+            .await
+            .unwrap();
+
+        let signature = client.sign_and_confirm_transaction(&transaction, None).await.unwrap();
+
+        let sent = memory.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].signatures[0], signature);
+        assert_eq!(sent[0].message.recent_blockhash, memory.state.read().unwrap().blockhash);
+        assert!(sent[0].verify().is_ok());
+    }
+
+    #[tokio::test]
+    async fn sign_and_confirm_transaction_includes_additional_signers() {
+        let memory = Arc::new(MemoryTransport::new());
+        let (client, payer) = client_with_signer(&memory);
+        let co_signer = Keypair::new();
+        let transaction = client
+            .create_unsigned_transaction(
+                Pubkey::new_unique(),
+                "list",
+                10u64,
+                vec![AccountMeta::new(payer, true), AccountMeta::new_readonly(co_signer.pubkey(), true)],
+            )
+            .await
+            .unwrap();
+
+        client.sign_and_confirm_transaction(&transaction, Some(&[&co_signer])).await.unwrap();
+
+        assert!(memory.sent_transactions()[0].verify().is_ok());
+    }
+
+    #[tokio::test]
+    async fn sign_and_confirm_transaction_surfaces_preflight_program_errors() {
+        let memory = Arc::new(MemoryTransport::new());
+        memory.set_simulation_error(Some(TransactionError::InstructionError(0, InstructionError::Custom(6000))));
+        let (client, payer) = client_with_signer(&memory);
+        let transaction = client
+            .create_unsigned_transaction(Pubkey::new_unique(), "list", 10u64, vec![AccountMeta::new(payer, true)])
+            .await
+            .unwrap();
+
+        let result = client.sign_and_confirm_transaction(&transaction, None).await;
+
+        assert!(matches!(result, Err(CnctdSolanaError::ProgramError { instruction_index: 0, code: 6000 })));
+        assert!(memory.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sign_and_confirm_transaction_needs_a_signer() {
+        let memory = Arc::new(MemoryTransport::new());
+        let client = CnctdSolana::with_transport(memory.clone()).unwrap();
+        let payer = Pubkey::new_unique();
+        let transaction = client
+            .create_unsigned_transaction(Pubkey::new_unique(), "list", 10u64, vec![AccountMeta::new(payer, true)])
+            .await
+            .unwrap();
+
+        let result = client.sign_and_confirm_transaction(&transaction, None).await;
+
+        assert!(matches!(result, Err(CnctdSolanaError::MissingSigner)));
+    }
+
+    #[tokio::test]
+    async fn simulate_transaction_reports_units_and_errors() {
+        let memory = Arc::new(MemoryTransport::new());
+        memory.set_units_consumed(12_345);
+        let (client, payer) = client_with_signer(&memory);
+        let transaction = client
+            .create_unsigned_transaction(Pubkey::new_unique(), "list", 10u64, vec![AccountMeta::new(payer, true)])
+            .await
+            .unwrap();
+
+        let simulation = client.simulate_transaction(&transaction, None).await.unwrap();
+        assert_eq!(simulation.units_consumed, Some(12_345));
+        assert!(simulation.err.is_none());
+
+        memory.set_simulation_error(Some(TransactionError::AccountInUse));
+        let simulation = client.simulate_transaction(&transaction, None).await.unwrap();
+        assert_eq!(simulation.err.map(TransactionError::from), Some(TransactionError::AccountInUse));
+        assert!(memory.sent_transactions().is_empty());
+    }
+}
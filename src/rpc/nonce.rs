@@ -0,0 +1,316 @@
+use borsh::BorshSerialize;
+use solana_client::{nonce_utils, rpc_config::RpcSendTransactionConfig};
+use solana_commitment_config::CommitmentConfig;
+use solana_nonce::state::State;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+
+use crate::rpc::{
+    client::CnctdSolana,
+    error::{CnctdSolanaError, Result},
+    ConfirmationOutcome,
+};
+
+/// Size of a nonce account's data
+pub const NONCE_ACCOUNT_SIZE: usize = State::size();
+
+/// A durable nonce account's current state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceAccount {
+    pub address: Pubkey,
+    /// Signs the `AdvanceNonceAccount` instruction of transactions using it
+    pub authority: Pubkey,
+    /// Stands in for the recent blockhash, and changes each time a
+    /// transaction using it lands
+    pub nonce: Hash,
+    pub lamports_per_signature: u64,
+}
+
+impl NonceAccount {
+    pub fn decode(address: Pubkey, account: &Account) -> Result<Self> {
+        let data = nonce_utils::data_from_account(account)
+            .map_err(|e| CnctdSolanaError::Deserialization(format!("nonce account {}: {}", address, e)))?;
+
+        Ok(Self {
+            address,
+            authority: data.authority,
+            nonce: data.blockhash(),
+            lamports_per_signature: data.get_lamports_per_signature(),
+        })
+    }
+}
+
+impl CnctdSolana {
+    /// Creates a nonce account at `nonce_keypair`'s address, funded with its
+    /// rent exemption by the instance signer, and advanced by `authority`
+    pub async fn create_nonce_account(&self, nonce_keypair: &Keypair, authority: &Pubkey) -> Result<Signature> {
+        let payer = self.signer_keypair.as_ref()
+            .ok_or(CnctdSolanaError::MissingSigner)?
+            .pubkey();
+        let lamports = self.get_minimum_balance_for_rent_exemption(NONCE_ACCOUNT_SIZE).await?;
+
+        let instructions = system_instruction::create_nonce_account(&payer, &nonce_keypair.pubkey(), authority, lamports);
+        let transaction = Transaction::new_with_payer(&instructions, Some(&payer));
+
+        self.sign_and_confirm_transaction(&transaction, Some(&[nonce_keypair])).await
+    }
+
+    pub async fn get_nonce_account(&self, address: &Pubkey) -> Result<NonceAccount> {
+        self.get_nonce_account_with_commitment(address, self.commitment).await
+    }
+
+    /// Always read from the RPC, as the nonce changes with every use
+    pub async fn get_nonce_account_with_commitment(&self, address: &Pubkey, commitment: CommitmentConfig) -> Result<NonceAccount> {
+        let account = self.get_multiple_accounts_with_commitment(&[*address], commitment).await?
+            .pop()
+            .flatten()
+            .ok_or(CnctdSolanaError::AccountNotFound(*address))?;
+
+        NonceAccount::decode(*address, &account)
+    }
+
+    /// Moves the nonce on, invalidating every transaction signed against
+    /// the current one. The instance signer must be the nonce authority.
+    pub async fn advance_nonce_account(&self, address: &Pubkey) -> Result<Signature> {
+        let authority = self.signer_keypair.as_ref()
+            .ok_or(CnctdSolanaError::MissingSigner)?
+            .pubkey();
+
+        let instruction = system_instruction::advance_nonce_account(address, &authority);
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&authority));
+
+        self.sign_and_confirm_transaction(&transaction, None).await
+    }
+
+    /// Unsigned transaction of `instructions` that uses the nonce in
+    /// `nonce_account` in place of a recent blockhash, so it stays valid
+    /// until the nonce is advanced rather than for ~60 seconds. Its first
+    /// instruction advances the nonce, which the nonce authority signs.
+    ///
+    /// Send it with `sign_and_confirm_nonce_transaction` or
+    /// `sign_and_send_nonce_transaction`, which keep the nonce;
+    /// `sign_and_send_transaction` would replace it with a recent blockhash.
+    pub async fn create_nonce_transaction(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        nonce_account: &Pubkey,
+    ) -> Result<Transaction> {
+        let nonce = self.get_nonce_account(nonce_account).await?;
+
+        let advance_nonce = system_instruction::advance_nonce_account(nonce_account, &nonce.authority);
+        let mut instructions = [std::slice::from_ref(&advance_nonce), instructions].concat();
+
+        if let Some(config) = &self.compute_budget {
+            // Measured with the nonce instruction, which then has to go back in front
+            instructions = self.apply_compute_budget(payer, &instructions, &[], config).await?;
+            if let Some(index) = instructions.iter().position(|instruction| *instruction == advance_nonce) {
+                let advance_nonce = instructions.remove(index);
+                instructions.insert(0, advance_nonce);
+            }
+        }
+
+        let mut message = Message::new(&instructions, Some(payer));
+        message.recent_blockhash = nonce.nonce;
+
+        Ok(Transaction::new_unsigned(message))
+    }
+
+    /// Like `create_unsigned_transaction`, using a durable nonce, see
+    /// `create_nonce_transaction`
+    pub async fn create_unsigned_nonce_transaction<T: BorshSerialize>(
+        &self,
+        program_id: Pubkey,
+        instruction_name: &str,
+        instruction_data: T,
+        accounts: Vec<AccountMeta>,
+        nonce_account: &Pubkey,
+    ) -> Result<Transaction> {
+        let payer_pubkey = accounts
+            .iter()
+            .find(|meta| meta.is_signer && meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .ok_or_else(|| CnctdSolanaError::InvalidInput("No writable signer found to act as payer".to_string()))?;
+
+        let instruction = self.create_instruction(program_id, instruction_name, instruction_data, accounts).await?;
+
+        self.create_nonce_transaction(&payer_pubkey, &[instruction], nonce_account).await
+    }
+
+    /// Like `sign_and_confirm_transaction`, for a transaction from
+    /// `create_nonce_transaction`: signs it against its nonce, then waits
+    /// until it lands or the nonce in `nonce_account` moves on without it
+    pub async fn sign_and_confirm_nonce_transaction(
+        &self,
+        transaction: &Transaction,
+        nonce_account: &Pubkey,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<Signature> {
+        self.sign_and_confirm_nonce_transaction_with_commitment(transaction, nonce_account, additional_signers, CommitmentConfig::confirmed()).await
+    }
+
+    /// Sends at the instance commitment and waits for `commitment`
+    pub async fn sign_and_confirm_nonce_transaction_with_commitment(
+        &self,
+        transaction: &Transaction,
+        nonce_account: &Pubkey,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let signature = self.sign_and_send_nonce_transaction(transaction, additional_signers).await?;
+        let nonce = transaction.message.recent_blockhash;
+
+        match self.confirm_nonce_transaction(&signature, nonce_account, &nonce, commitment).await? {
+            ConfirmationOutcome::Confirmed(..) => Ok(signature),
+            ConfirmationOutcome::Failed(err) => Err(err.into()),
+            ConfirmationOutcome::Expired => Err(CnctdSolanaError::NonceAdvanced),
+        }
+    }
+
+    /// Signs `transaction` keeping the nonce it carries as its blockhash,
+    /// and sends it without waiting, for use with `confirm_nonce_transaction`.
+    /// Signatures already on it, e.g. from a wallet, are kept, and any
+    /// signer not given here must have signed it already.
+    #[tracing::instrument(level = "debug", skip_all, fields(signature = tracing::field::Empty))]
+    pub async fn sign_and_send_nonce_transaction(
+        &self,
+        transaction: &Transaction,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<Signature> {
+        let signer_keypair = self.signer_keypair.as_ref()
+            .ok_or(CnctdSolanaError::MissingSigner)?;
+        let mut signers = vec![signer_keypair];
+        signers.extend(additional_signers.unwrap_or_default());
+
+        let mut signed_transaction = transaction.clone();
+        signed_transaction.try_partial_sign(&signers, transaction.message.recent_blockhash)
+            .map_err(|e| CnctdSolanaError::InvalidInput(format!("Could not sign transaction: {}", e)))?;
+        if !signed_transaction.is_signed() {
+            return Err(CnctdSolanaError::InvalidInput("Transaction is missing signatures from other signers".to_string()));
+        }
+
+        let config = RpcSendTransactionConfig {
+            preflight_commitment: Some(self.commitment.commitment),
+            min_context_slot: self.slot_tracker.min_context_slot(self.commitment, None),
+            ..RpcSendTransactionConfig::default()
+        };
+        let signed_transaction = &signed_transaction;
+        let signature = self.pool.call("send_transaction", |transport| async move {
+            transport.send_transaction_with_config(signed_transaction, config).await
+        }).await?;
+        tracing::Span::current().record("signature", tracing::field::display(&signature));
+
+        Ok(signature)
+    }
+
+    /// Waits for `signature` to reach `commitment`, or for the nonce in
+    /// `nonce_account` to move on from `nonce` without it landing, which
+    /// takes the place of the blockhash expiring in `confirm_transaction`.
+    /// The transaction's own nonce instruction also moves it on, so the
+    /// status is checked once more before reporting `Expired`.
+    #[tracing::instrument(level = "debug", skip_all, fields(%signature, %nonce_account, commitment = ?commitment.commitment))]
+    pub async fn confirm_nonce_transaction(
+        &self,
+        signature: &Signature,
+        nonce_account: &Pubkey,
+        nonce: &Hash,
+        commitment: CommitmentConfig,
+    ) -> Result<ConfirmationOutcome> {
+        self.confirm_until_expired(signature, commitment, || async move {
+            let current = self.get_nonce_account_with_commitment(nonce_account, commitment).await?;
+
+            Ok(current.nonce != *nonce)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use solana_nonce::{
+        state::{Data, DurableNonce},
+        versions::Versions,
+    };
+
+    use super::*;
+    use crate::rpc::{MemoryTransport, RpcEndpoint, RpcMode, RpcPool};
+
+    fn client(memory: &Arc<MemoryTransport>, signer: Keypair) -> CnctdSolana {
+        let endpoint = RpcEndpoint::with_transport(memory.clone());
+        let mut client = CnctdSolana::with_pool(RpcPool::from_endpoints(vec![endpoint], RpcMode::Failover)).unwrap();
+        client.signer_keypair = Some(signer);
+        client
+    }
+
+    fn set_nonce(memory: &MemoryTransport, address: Pubkey, authority: Pubkey, nonce: Hash) {
+        let data = Data::new(authority, DurableNonce::from_blockhash(&nonce), 5_000);
+        let state = Versions::new(State::Initialized(data));
+        let account = Account {
+            lamports: 1_447_680,
+            data: bincode::serialize(&state).unwrap(),
+            owner: solana_system_interface::program::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        memory.set_account(address, account);
+    }
+
+    #[tokio::test]
+    async fn sends_with_the_nonce_and_expires_once_it_advances() {
+        let memory = Arc::new(MemoryTransport::new());
+        let signer = Keypair::new();
+        let payer = signer.pubkey();
+        let nonce_account = Pubkey::new_unique();
+        set_nonce(&memory, nonce_account, payer, Hash::new_unique());
+        let client = client(&memory, signer);
+
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+        let transaction = client.create_nonce_transaction(&payer, &[transfer], &nonce_account).await.unwrap();
+        let nonce = transaction.message.recent_blockhash;
+        assert_eq!(nonce, client.get_nonce_account(&nonce_account).await.unwrap().nonce);
+
+        client.sign_and_confirm_nonce_transaction(&transaction, &nonce_account, None).await.unwrap();
+        assert_eq!(memory.sent_transactions()[0].message.recent_blockhash, nonce);
+
+        // Another transaction used the nonce first, so this one can never land
+        set_nonce(&memory, nonce_account, payer, Hash::new_unique());
+        let outcome = client.confirm_nonce_transaction(&Signature::new_unique(), &nonce_account, &nonce, CommitmentConfig::confirmed()).await.unwrap();
+        assert!(matches!(outcome, ConfirmationOutcome::Expired));
+    }
+
+    #[tokio::test]
+    async fn keeps_signatures_a_wallet_already_added() {
+        let memory = Arc::new(MemoryTransport::new());
+        let authority = Keypair::new();
+        let wallet = Keypair::new();
+        let nonce_account = Pubkey::new_unique();
+        set_nonce(&memory, nonce_account, authority.pubkey(), Hash::new_unique());
+        let client = client(&memory, authority);
+
+        let transfer = system_instruction::transfer(&wallet.pubkey(), &Pubkey::new_unique(), 1);
+        let mut transaction = client.create_nonce_transaction(&wallet.pubkey(), &[transfer], &nonce_account).await.unwrap();
+
+        // Without the wallet's signature it cannot be sent
+        let unsigned = client.sign_and_send_nonce_transaction(&transaction, None).await;
+        assert!(matches!(unsigned, Err(CnctdSolanaError::InvalidInput(_))));
+
+        let nonce = transaction.message.recent_blockhash;
+        transaction.partial_sign(&[&wallet], nonce);
+        client.sign_and_confirm_nonce_transaction(&transaction, &nonce_account, None).await.unwrap();
+
+        let sent = &memory.sent_transactions()[0];
+        assert!(sent.is_signed());
+        assert_eq!(sent.signatures[0], transaction.signatures[0]);
+    }
+}
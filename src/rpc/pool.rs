@@ -0,0 +1,877 @@
+use std::{future::Future, path::{Path, PathBuf}, sync::Arc, time::{Duration, Instant}};
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use reqwest_middleware::ClientWithMiddleware;
+use serde::{Deserialize, Serialize};
+use solana_client::{client_error::Result as ClientResult, rpc_client::RpcClientConfig};
+use solana_rpc_client::http_sender::HttpSender;
+use tracing::{field, Instrument};
+
+use crate::rpc::{
+    error::{CnctdSolanaError, Result},
+    health::{EndpointHealth, EndpointHealthSnapshot, EndpointStatus, HealthConfig},
+    rate_limit::{RateLimit, RateLimiter},
+    rpc_url::{RpcUrl, RpcUrlExt},
+    fixture::{RecordingSender, ReplaySender},
+    http::HttpConfig,
+    transport::{HttpTransport, RpcTransport},
+    DomainExtractor, RpcMode,
+};
+
+/// Per-call timeout applied to each endpoint attempt unless overridden
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// How many times a call that every endpoint rate limited is retried
+pub const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// How many times a read is retried when every endpoint is behind its
+/// `min_context_slot`, waiting `MIN_CONTEXT_SLOT_RETRY_DELAY` (about a
+/// slot) in between
+const MIN_CONTEXT_SLOT_RETRIES: u32 = 3;
+const MIN_CONTEXT_SLOT_RETRY_DELAY: Duration = Duration::from_millis(400);
+
+/// When `RpcMode::Hedged` sends a backup request. The delay is the
+/// in-flight endpoint's latency at `percentile`, clamped to
+/// `min_delay..=max_delay`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct HedgeConfig {
+    pub percentile: u8,          // Latency percentile (0-100) an endpoint may take before a backup is sent
+    pub default_delay: Duration, // Used until the endpoint has latency samples
+    pub min_delay: Duration,     // Floor, so a fast endpoint's jitter does not trigger backups
+    pub max_delay: Duration,     // Ceiling, so a slow endpoint is not waited on for long
+    pub max_hedges: usize,       // Most backups sent per call, on top of failing over after errors
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 95,
+            default_delay: Duration::from_millis(500),
+            min_delay: Duration::from_millis(20),
+            max_delay: Duration::from_secs(2),
+            max_hedges: 1,
+        }
+    }
+}
+
+impl HedgeConfig {
+    pub fn delay(&self, endpoint: &RpcEndpoint) -> Duration {
+        endpoint.health
+            .latency_percentile(self.percentile)
+            .unwrap_or(self.default_delay)
+            .clamp(self.min_delay, self.max_delay.max(self.min_delay))
+    }
+}
+
+/// Provider-specific APIs an endpoint serves beyond standard Solana JSON-RPC
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcCapability {
+    /// Helius `getPriorityFeeEstimate`
+    PriorityFeeEstimate,
+    /// Digital Asset Standard `getAsset`, `getAssetsByOwner`, `searchAssets`
+    DigitalAssetStandard,
+}
+
+pub struct RpcEndpoint {
+    pub url: String,
+    pub ws_url: String,
+    pub transport: Arc<dyn RpcTransport>,
+    pub health: EndpointHealth,
+    pub rate_limiter: RateLimiter,
+    pub capabilities: Vec<RpcCapability>,
+    /// Divides the endpoint's health score when ranking, so an endpoint of
+    /// weight 2 is preferred over one of weight 1 unless that one is more
+    /// than twice as fast. Zero counts as one.
+    pub weight: u32,
+}
+
+impl RpcEndpoint {
+    /// An endpoint with its own HTTP client; pools share one client across
+    /// their endpoints through `with_http_client`
+    pub fn new(url: &str) -> Self {
+        Self::with_http_client(url, HttpConfig::default().build())
+    }
+
+    /// Sends over `client`, sharing its connection pool with every other
+    /// endpoint built from the same client
+    pub fn with_http_client(url: &str, client: ClientWithMiddleware) -> Self {
+        let sender = HttpSender::new_with_client_with_middleware(url, client);
+
+        Self::with_transport(Arc::new(HttpTransport::new(sender, RpcClientConfig::default())))
+    }
+
+    /// Calls `url`, writing every request and response to the fixture at
+    /// `path` for `replaying` later
+    pub fn recording(url: &str, path: impl Into<PathBuf>) -> Self {
+        let sender = RecordingSender::new(
+            HttpSender::new_with_client_with_middleware(url, HttpConfig::default().build()),
+            path,
+        );
+
+        Self::with_transport(Arc::new(HttpTransport::new(sender, RpcClientConfig::default())))
+    }
+
+    /// Answers from the fixture at `path` with no network access. The
+    /// endpoint keeps the recorded URL, so domain-based capabilities match
+    /// the recording, but is not rate limited. Subscriptions are not
+    /// recorded and will not connect.
+    pub fn replaying(path: impl AsRef<Path>) -> Result<Self> {
+        let sender = ReplaySender::from_file(path)?;
+        let endpoint = Self::with_transport(Arc::new(HttpTransport::new(sender, RpcClientConfig::default())));
+
+        Ok(endpoint.with_ws_url("").with_rate_limit(None))
+    }
+
+    pub fn with_transport(transport: Arc<dyn RpcTransport>) -> Self {
+        let url = transport.url();
+
+        Self {
+            rate_limiter: RateLimiter::new(RpcUrl::default_rate_limit(&url)),
+            capabilities: RpcUrl::default_capabilities(&url),
+            ws_url: url.to_ws(),
+            url,
+            transport,
+            health: EndpointHealth::new(),
+            weight: 1,
+        }
+    }
+
+    /// For providers whose websocket endpoint is not the HTTP URL with a
+    /// `ws` scheme
+    pub fn with_ws_url(mut self, ws_url: &str) -> Self {
+        self.ws_url = ws_url.to_string();
+        self
+    }
+
+    pub fn with_rate_limit(self, limit: Option<RateLimit>) -> Self {
+        self.rate_limiter.set_limit(limit);
+        self
+    }
+
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Replaces the capabilities inferred from the endpoint's domain, e.g.
+    /// for a proxy in front of a provider
+    pub fn with_capabilities(mut self, capabilities: Vec<RpcCapability>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn supports(&self, capability: RpcCapability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+
+    pub fn domain(&self) -> String {
+        self.url.domain()
+    }
+}
+
+/// A set of RPC endpoints that calls are routed across, either one after
+/// another (`Failover`), all at once (`Concurrent`), or one at a time with
+/// backups for slow answers (`Hedged`).
+///
+/// Endpoints are tried best-first according to their measured health, with
+/// the order they were given in used as the tie-breaker.
+#[derive(Clone)]
+pub struct RpcPool {
+    endpoints: Vec<Arc<RpcEndpoint>>,
+    mode: RpcMode,
+    timeout: Duration,
+    health_config: HealthConfig,
+    hedge_config: HedgeConfig,
+    rate_limit_retries: u32,
+}
+
+impl RpcPool {
+    pub fn new(rpc_urls: Vec<String>, mode: RpcMode) -> Self {
+        Self::with_http_config(rpc_urls, mode, HttpConfig::default())
+    }
+
+    /// A pool whose endpoints all send over one HTTP client built from
+    /// `http_config`, so every call reuses its keep-alive connections
+    pub fn with_http_config(rpc_urls: Vec<String>, mode: RpcMode, http_config: HttpConfig) -> Self {
+        let client = http_config.build();
+
+        Self {
+            endpoints: rpc_urls.iter().map(|url| Arc::new(RpcEndpoint::with_http_client(url, client.clone()))).collect(),
+            mode,
+            timeout: DEFAULT_RPC_TIMEOUT,
+            health_config: HealthConfig::default(),
+            hedge_config: HedgeConfig::default(),
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+        }
+    }
+
+    pub fn from_transports(transports: Vec<Arc<dyn RpcTransport>>, mode: RpcMode) -> Self {
+        Self {
+            endpoints: transports.into_iter().map(|transport| Arc::new(RpcEndpoint::with_transport(transport))).collect(),
+            mode,
+            timeout: DEFAULT_RPC_TIMEOUT,
+            health_config: HealthConfig::default(),
+            hedge_config: HedgeConfig::default(),
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+        }
+    }
+
+    pub fn from_endpoints(endpoints: Vec<RpcEndpoint>, mode: RpcMode) -> Self {
+        Self {
+            endpoints: endpoints.into_iter().map(Arc::new).collect(),
+            mode,
+            timeout: DEFAULT_RPC_TIMEOUT,
+            health_config: HealthConfig::default(),
+            hedge_config: HedgeConfig::default(),
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+        }
+    }
+
+    pub fn single(rpc_url: &str) -> Self {
+        Self::new(vec![rpc_url.to_string()], RpcMode::Failover)
+    }
+
+    pub fn fast() -> Self {
+        Self::new(RpcUrl::speed_priority(), RpcMode::Concurrent)
+    }
+
+    pub fn conservative() -> Self {
+        Self::new(RpcUrl::volume_priority(), RpcMode::Failover)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: RpcMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_health_config(mut self, health_config: HealthConfig) -> Self {
+        self.health_config = health_config;
+        self
+    }
+
+    /// Sets when `RpcMode::Hedged` sends backup requests
+    pub fn with_hedge_config(mut self, hedge_config: HedgeConfig) -> Self {
+        self.hedge_config = hedge_config;
+        self
+    }
+
+    /// Overrides the token bucket for every endpoint whose URL or domain
+    /// matches `url_or_domain`; `None` removes the limit.
+    pub fn with_rate_limit(self, url_or_domain: &str, limit: Option<RateLimit>) -> Self {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.url == url_or_domain || endpoint.domain() == url_or_domain)
+            .for_each(|endpoint| endpoint.rate_limiter.set_limit(limit));
+        self
+    }
+
+    pub fn with_rate_limit_retries(mut self, rate_limit_retries: u32) -> Self {
+        self.rate_limit_retries = rate_limit_retries;
+        self
+    }
+
+    pub fn mode(&self) -> RpcMode {
+        self.mode
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn endpoints(&self) -> &[Arc<RpcEndpoint>] {
+        &self.endpoints
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.endpoints.iter().map(|endpoint| endpoint.url.clone()).collect()
+    }
+
+    /// Websocket URLs in ranked order, for subscriptions. Endpoints with an
+    /// empty websocket URL are left out.
+    pub fn ws_urls(&self) -> Vec<String> {
+        self.ranked_endpoints()
+            .iter()
+            .filter(|endpoint| !endpoint.ws_url.is_empty())
+            .map(|endpoint| endpoint.ws_url.clone())
+            .collect()
+    }
+
+    /// A pool over the endpoints matching `predicate`, sharing their health
+    /// and rate limits with this pool
+    pub fn filter(&self, predicate: impl Fn(&RpcEndpoint) -> bool) -> Self {
+        Self {
+            endpoints: self.endpoints.iter().filter(|endpoint| predicate(endpoint)).cloned().collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn primary(&self) -> Option<&Arc<RpcEndpoint>> {
+        self.endpoints.first()
+    }
+
+    /// The endpoint the next call will try first
+    pub fn best_endpoint(&self) -> Option<&Arc<RpcEndpoint>> {
+        let best = self.ranked_endpoints().into_iter().next()?;
+
+        self.endpoints.iter().find(|endpoint| Arc::ptr_eq(endpoint, &best))
+    }
+
+    /// Endpoints in the order the next call will try them: healthy endpoints
+    /// by score over weight, then endpoints due a recovery probe, then those still
+    /// cooling down as a last resort. Within each group, endpoints backing
+    /// off after a 429 go last.
+    pub fn ranked_endpoints(&self) -> Vec<Arc<RpcEndpoint>> {
+        let mut ranked: Vec<(EndpointStatus, bool, f64, usize, Arc<RpcEndpoint>)> = self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let status = endpoint.health.status();
+                let backing_off = endpoint.rate_limiter.backoff_remaining().is_some();
+                let score = endpoint.health.score(&self.health_config) / endpoint.weight.max(1) as f64;
+                (status, backing_off, score, index, endpoint.clone())
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.cmp(&b.1))
+                .then(a.2.total_cmp(&b.2))
+                .then(a.3.cmp(&b.3))
+        });
+
+        ranked.into_iter().map(|(_, _, _, _, endpoint)| endpoint).collect()
+    }
+
+    /// Health of every endpoint, in ranked order, for logging and alerting
+    pub fn health(&self) -> Vec<EndpointHealthSnapshot> {
+        self.ranked_endpoints()
+            .iter()
+            .map(|endpoint| endpoint.health.snapshot(&endpoint.url, &endpoint.domain(), &self.health_config))
+            .collect()
+    }
+
+    /// Runs `transport_call` against the pool's endpoints according to its mode.
+    ///
+    /// Each attempt waits for the endpoint's rate limiter and is bounded by
+    /// the pool timeout. Errors that another node would answer the same way
+    /// (account not found, transaction errors) are returned immediately
+    /// instead of being retried on the next endpoint. When every endpoint
+    /// rate limits the call, it is retried once their backoff has elapsed,
+    /// and when every endpoint is behind the read's `min_context_slot`, it
+    /// is retried after a short wait.
+    ///
+    /// The call runs in an `rpc_call` span, and each endpoint attempt in a
+    /// nested `rpc_attempt` span, recording latency and result.
+    pub async fn call<T, F, Fut>(&self, method: &str, transport_call: F) -> Result<T>
+    where
+        F: Fn(Arc<dyn RpcTransport>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let span = tracing::debug_span!(
+            "rpc_call",
+            method,
+            mode = ?self.mode,
+            latency_ms = field::Empty,
+            result = field::Empty,
+        );
+        let start = Instant::now();
+
+        let result = self.call_with_retries(method, &transport_call).instrument(span.clone()).await;
+
+        record_result(&span, start, &result);
+        result
+    }
+
+    /// Runs `transport_call` against every endpoint at once, whatever the
+    /// pool's mode, for sends that should reach as many nodes as possible.
+    /// Endpoints cooling down are skipped unless nothing else is left.
+    ///
+    /// Returns the first success without waiting for slower endpoints,
+    /// whose attempts finish in the background, or the error of the
+    /// best-ranked endpoint when none succeeded. Runs in an
+    /// `rpc_broadcast` span.
+    pub async fn broadcast<T, F, Fut>(&self, method: &str, transport_call: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(Arc<dyn RpcTransport>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ClientResult<T>> + Send,
+    {
+        if self.endpoints.is_empty() {
+            return Err(CnctdSolanaError::Config("RPC pool has no endpoints".to_string()));
+        }
+
+        let endpoints = self.ranked_endpoints();
+        let targets = available(&endpoints);
+        let span = tracing::debug_span!(
+            "rpc_broadcast",
+            method,
+            endpoints = targets.len(),
+            latency_ms = field::Empty,
+            result = field::Empty,
+        );
+        let start = Instant::now();
+
+        // Spawned, so that attempts still running when one succeeds are
+        // not cancelled halfway through sending
+        let transport_call = Arc::new(transport_call);
+        let mut attempts: FuturesUnordered<_> = targets
+            .iter()
+            .enumerate()
+            .map(|(rank, endpoint)| {
+                let pool = self.clone();
+                let endpoint = endpoint.clone();
+                let method = method.to_string();
+                let transport_call = transport_call.clone();
+                let attempt = tokio::spawn(
+                    async move { pool.attempt(&endpoint, &method, &*transport_call).await }.instrument(span.clone()),
+                );
+
+                async move { (rank, attempt.await) }
+            })
+            .collect();
+
+        let mut best_error: Option<(usize, CnctdSolanaError)> = None;
+        let result = loop {
+            match attempts.next().await {
+                Some((_, Ok(Ok(data)))) => break Ok(data),
+                Some((rank, Ok(Err(e)))) => {
+                    if best_error.as_ref().is_none_or(|(best, _)| rank < *best) {
+                        best_error = Some((rank, e));
+                    }
+                }
+                Some((_, Err(e))) => std::panic::resume_unwind(e.into_panic()),
+                None => break Err(best_error.map(|(_, e)| e).expect("at least one endpoint was attempted")),
+            }
+        };
+
+        record_result(&span, start, &result);
+        result
+    }
+
+    async fn call_with_retries<T, F, Fut>(&self, method: &str, transport_call: &F) -> Result<T>
+    where
+        F: Fn(Arc<dyn RpcTransport>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        if self.endpoints.is_empty() {
+            return Err(CnctdSolanaError::Config("RPC pool has no endpoints".to_string()));
+        }
+
+        let mut rate_limit_retries = 0;
+        let mut min_context_slot_retries = 0;
+
+        loop {
+            match self.dispatch(method, transport_call).await {
+                Err(CnctdSolanaError::RateLimited { .. }) if rate_limit_retries < self.rate_limit_retries => {
+                    // The next attempt's `acquire` waits out the endpoint's backoff
+                    rate_limit_retries += 1;
+                }
+                Err(CnctdSolanaError::MinContextSlotNotReached) if min_context_slot_retries < MIN_CONTEXT_SLOT_RETRIES => {
+                    // Every endpoint is behind; give them a slot to catch up
+                    min_context_slot_retries += 1;
+                    tokio::time::sleep(MIN_CONTEXT_SLOT_RETRY_DELAY).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn dispatch<T, F, Fut>(&self, method: &str, transport_call: &F) -> Result<T>
+    where
+        F: Fn(Arc<dyn RpcTransport>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let endpoints = self.ranked_endpoints();
+
+        match self.mode {
+            RpcMode::Failover => {
+                let mut last_error = None;
+
+                for endpoint in &endpoints {
+                    match self.attempt(endpoint, method, transport_call).await {
+                        Ok(data) => return Ok(data),
+                        Err(e) if e.is_retryable() => last_error = Some(e),
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                Err(last_error.expect("at least one endpoint was attempted"))
+            }
+
+            RpcMode::Concurrent => {
+                let mut tasks: FuturesUnordered<_> = available(&endpoints)
+                    .iter()
+                    .map(|endpoint| self.attempt(endpoint, method, transport_call))
+                    .collect();
+
+                let mut last_error = None;
+
+                // Dropping `tasks` on return cancels the attempts still in flight
+                while let Some(result) = tasks.next().await {
+                    match result {
+                        Ok(data) => return Ok(data),
+                        Err(e) if e.is_retryable() => last_error = Some(e),
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                Err(last_error.expect("at least one endpoint was attempted"))
+            }
+
+            RpcMode::Hedged => self.dispatch_hedged(&endpoints, method, transport_call).await,
+        }
+    }
+
+    /// Sends to the best endpoint and, each time the newest attempt has gone
+    /// unanswered for its hedge delay, to the next one as a backup, up to
+    /// `max_hedges` backups. Backups only go to endpoints that are neither
+    /// cooling down nor backing off after a 429, since adding load there
+    /// would not get an answer sooner. An attempt failing with a retryable
+    /// error while nothing else is in flight fails over straight away, to
+    /// any endpoint. Attempts still in flight are cancelled once one succeeds.
+    async fn dispatch_hedged<T, F, Fut>(&self, endpoints: &[Arc<RpcEndpoint>], method: &str, transport_call: &F) -> Result<T>
+    where
+        F: Fn(Arc<dyn RpcTransport>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut remaining = endpoints.iter();
+        let mut in_flight = FuturesUnordered::new();
+        let mut hedges = 0;
+        let mut last_error = None;
+
+        let first = remaining.next().expect("pool has endpoints");
+        let mut hedge_delay = self.hedge_config.delay(first);
+        in_flight.push(self.attempt(first, method, transport_call));
+
+        // Only restarted when an attempt is launched, so results arriving
+        // from other attempts do not push the hedge back
+        let hedge_timer = tokio::time::sleep(hedge_delay);
+        tokio::pin!(hedge_timer);
+
+        loop {
+            let can_hedge = hedges < self.hedge_config.max_hedges
+                && remaining.as_slice().first().is_some_and(|endpoint| can_take_hedge(endpoint));
+
+            tokio::select! {
+                result = in_flight.next() => match result {
+                    Some(Ok(data)) => return Ok(data),
+                    Some(Err(e)) if e.is_retryable() => {
+                        last_error = Some(e);
+
+                        if in_flight.is_empty() {
+                            match remaining.next() {
+                                Some(endpoint) => {
+                                    hedge_delay = self.hedge_config.delay(endpoint);
+                                    hedge_timer.as_mut().reset(tokio::time::Instant::now() + hedge_delay);
+                                    in_flight.push(self.attempt(endpoint, method, transport_call));
+                                }
+                                None => break,
+                            }
+                        }
+                    }
+                    Some(Err(e)) => return Err(e),
+                    None => break,
+                },
+                _ = &mut hedge_timer, if can_hedge => {
+                    let endpoint = remaining.next().expect("checked by can_hedge");
+                    tracing::debug!(
+                        method,
+                        domain = %endpoint.domain(),
+                        delay_ms = hedge_delay.as_millis() as u64,
+                        "Sending hedged RPC request"
+                    );
+
+                    hedges += 1;
+                    hedge_delay = self.hedge_config.delay(endpoint);
+                    hedge_timer.as_mut().reset(tokio::time::Instant::now() + hedge_delay);
+                    in_flight.push(self.attempt(endpoint, method, transport_call));
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one endpoint was attempted"))
+    }
+
+    async fn attempt<T, F, Fut>(
+        &self,
+        endpoint: &RpcEndpoint,
+        method: &str,
+        transport_call: &F,
+    ) -> Result<T>
+    where
+        F: Fn(Arc<dyn RpcTransport>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let span = tracing::debug_span!(
+            "rpc_attempt",
+            method,
+            domain = %endpoint.domain(),
+            latency_ms = field::Empty,
+            result = field::Empty,
+        );
+        let start = Instant::now();
+
+        let result = self.attempt_endpoint(endpoint, method, transport_call).instrument(span.clone()).await;
+
+        record_result(&span, start, &result);
+        result
+    }
+
+    async fn attempt_endpoint<T, F, Fut>(
+        &self,
+        endpoint: &RpcEndpoint,
+        method: &str,
+        transport_call: &F,
+    ) -> Result<T>
+    where
+        F: Fn(Arc<dyn RpcTransport>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        endpoint.rate_limiter.acquire().await;
+        let start = Instant::now();
+
+        match tokio::time::timeout(self.timeout, transport_call(endpoint.transport.clone())).await {
+            Ok(Ok(data)) => {
+                endpoint.health.record_success(start.elapsed(), &self.health_config);
+                endpoint.rate_limiter.record_success();
+                Ok(data)
+            }
+            Ok(Err(e)) => {
+                let error = CnctdSolanaError::from(e);
+                if let CnctdSolanaError::RateLimited { retry_after } = &error {
+                    // Being throttled says nothing about the endpoint's health
+                    let backoff = endpoint.rate_limiter.back_off(*retry_after);
+                    tracing::warn!(backoff_ms = backoff.as_millis() as u64, "Rate limited, backing off");
+                    return Err(error);
+                }
+                if !error.is_retryable() {
+                    // The node answered; the request itself was at fault
+                    endpoint.health.record_success(start.elapsed(), &self.health_config);
+                    return Err(error);
+                }
+                endpoint.health.record_error(start.elapsed(), &error.to_string(), &self.health_config);
+                tracing::warn!(error = %error, "RPC call failed");
+                Err(error)
+            }
+            Err(_) => {
+                endpoint.health.record_timeout(self.timeout, &self.health_config);
+                tracing::warn!(timeout_ms = self.timeout.as_millis() as u64, "RPC call timed out");
+                Err(CnctdSolanaError::Timeout(format!("{} call to {} exceeded {:?}", method, endpoint.domain(), self.timeout)))
+            }
+        }
+    }
+}
+
+/// Ranked `endpoints` without those still cooling down, which only take
+/// part when nothing else is left
+fn available(endpoints: &[Arc<RpcEndpoint>]) -> &[Arc<RpcEndpoint>] {
+    let available = endpoints
+        .iter()
+        .filter(|endpoint| endpoint.health.status() != EndpointStatus::CoolingDown)
+        .count();
+
+    if available > 0 { &endpoints[..available] } else { endpoints }
+}
+
+/// Whether a backup request may go to `endpoint`
+fn can_take_hedge(endpoint: &RpcEndpoint) -> bool {
+    endpoint.health.status() != EndpointStatus::CoolingDown && endpoint.rate_limiter.backoff_remaining().is_none()
+}
+
+/// Records `latency_ms` and `result` ("ok" or the error) on a call span
+fn record_result<T>(span: &tracing::Span, start: Instant, result: &Result<T>) {
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    match result {
+        Ok(_) => span.record("result", "ok"),
+        Err(e) => span.record("result", field::display(e)),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_commitment_config::CommitmentConfig;
+
+    use super::*;
+    use crate::rpc::MemoryTransport;
+
+    #[test]
+    fn weight_scales_the_health_score() {
+        let fast = RpcEndpoint::with_transport(Arc::new(MemoryTransport::new()));
+        let slow = RpcEndpoint::with_transport(Arc::new(MemoryTransport::new())).with_weight(2);
+        let config = HealthConfig::default();
+        fast.health.record_success(Duration::from_millis(100), &config);
+        slow.health.record_success(Duration::from_millis(150), &config);
+
+        let pool = RpcPool::from_endpoints(vec![fast, slow], RpcMode::Failover);
+
+        assert_eq!(pool.ranked_endpoints()[0].weight, 2);
+    }
+
+    #[test]
+    fn ranks_by_status_then_backoff_then_score() {
+        let config = HealthConfig::default();
+        let cooling = memory_endpoint(1);
+        for _ in 0..config.failure_threshold {
+            cooling.health.record_error(Duration::from_millis(1), "down", &config);
+        }
+        let backing_off = memory_endpoint(2);
+        backing_off.rate_limiter.back_off(Some(Duration::from_secs(60)));
+        let slow = memory_endpoint(3);
+        slow.health.record_success(Duration::from_millis(300), &config);
+        let fast = memory_endpoint(4);
+        fast.health.record_success(Duration::from_millis(100), &config);
+
+        let pool = RpcPool::from_endpoints(vec![cooling, backing_off, slow, fast], RpcMode::Failover);
+        let order: Vec<usize> = pool.ranked_endpoints()
+            .iter()
+            .map(|ranked| pool.endpoints().iter().position(|endpoint| Arc::ptr_eq(endpoint, ranked)).unwrap())
+            .collect();
+
+        assert_eq!(order, vec![3, 2, 1, 0]);
+    }
+
+    /// Accepts connections and never answers
+    async fn hanging_endpoint() -> RpcEndpoint {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        RpcEndpoint::new(&url).with_rate_limit(None)
+    }
+
+    fn memory_endpoint(slot: u64) -> RpcEndpoint {
+        let memory = MemoryTransport::new();
+        memory.advance_slot(slot - 1);
+
+        RpcEndpoint::with_transport(Arc::new(memory))
+    }
+
+    fn attempts(endpoint: &RpcEndpoint) -> u64 {
+        let health = endpoint.health.snapshot(&endpoint.url, &endpoint.domain(), &HealthConfig::default());
+        health.successes + health.errors + health.timeouts
+    }
+
+    fn hedged(endpoints: Vec<RpcEndpoint>, max_hedges: usize) -> RpcPool {
+        let hedge_config = HedgeConfig { default_delay: Duration::from_millis(50), max_hedges, ..HedgeConfig::default() };
+
+        RpcPool::from_endpoints(endpoints, RpcMode::Hedged).with_hedge_config(hedge_config)
+    }
+
+    async fn get_slot(pool: &RpcPool) -> Result<u64> {
+        pool.call("get_slot", |transport| async move {
+            transport.get_slot_with_commitment(CommitmentConfig::confirmed()).await
+        }).await
+    }
+
+    #[tokio::test]
+    async fn hedges_a_slow_endpoint_after_the_delay() {
+        let pool = hedged(vec![hanging_endpoint().await, memory_endpoint(1)], 1);
+
+        let start = Instant::now();
+        let slot = tokio::time::timeout(Duration::from_secs(5), get_slot(&pool)).await.unwrap().unwrap();
+
+        assert_eq!(slot, 1);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        // The hanging attempt was cancelled, not timed out
+        assert_eq!(attempts(&pool.endpoints()[0]), 0);
+    }
+
+    #[tokio::test]
+    async fn first_success_wins_before_further_hedges() {
+        let pool = hedged(vec![hanging_endpoint().await, memory_endpoint(1), memory_endpoint(2)], 2);
+
+        let slot = tokio::time::timeout(Duration::from_secs(5), get_slot(&pool)).await.unwrap().unwrap();
+
+        assert_eq!(slot, 1);
+        assert_eq!(attempts(&pool.endpoints()[2]), 0);
+    }
+
+    #[tokio::test]
+    async fn hedging_does_not_fan_out_request_errors() {
+        let pool = hedged(vec![memory_endpoint(1), memory_endpoint(1)], 1);
+
+        let result = pool.call("unknown", |transport| async move {
+            transport.send_json_request("unknownMethod", serde_json::json!([])).await
+        }).await;
+
+        assert!(!result.unwrap_err().is_retryable());
+        assert_eq!(attempts(&pool.endpoints()[0]), 1);
+        assert_eq!(attempts(&pool.endpoints()[1]), 0);
+    }
+
+    #[tokio::test]
+    async fn hedges_skip_endpoints_cooling_down() {
+        let cooling = memory_endpoint(1);
+        for _ in 0..HealthConfig::default().failure_threshold {
+            cooling.health.record_error(Duration::from_millis(1), "down", &HealthConfig::default());
+        }
+        let pool = hedged(vec![hanging_endpoint().await, cooling], 1).with_timeout(Duration::from_millis(300));
+
+        // Reached only by failing over once the hanging attempt timed out
+        let slot = tokio::time::timeout(Duration::from_secs(5), get_slot(&pool)).await.unwrap().unwrap();
+
+        assert_eq!(slot, 1);
+        let hanging = &pool.endpoints()[0];
+        assert_eq!(hanging.health.snapshot(&hanging.url, &hanging.domain(), &HealthConfig::default()).timeouts, 1);
+    }
+
+    async fn get_account_at(pool: &RpcPool, min_context_slot: u64) -> Result<u64> {
+        let config = solana_client::rpc_config::RpcAccountInfoConfig {
+            min_context_slot: Some(min_context_slot),
+            ..Default::default()
+        };
+
+        pool.call("get_account", |transport| {
+            let config = config.clone();
+            async move { transport.get_account_with_config(&solana_sdk::pubkey::Pubkey::new_unique(), config).await }
+        }).await.map(|response| response.context.slot)
+    }
+
+    #[tokio::test]
+    async fn reads_move_past_a_lagging_endpoint() {
+        let pool = RpcPool::from_endpoints(vec![memory_endpoint(5), memory_endpoint(10)], RpcMode::Failover);
+
+        assert_eq!(get_account_at(&pool, 10).await.unwrap(), 10);
+        assert_eq!(attempts(&pool.endpoints()[0]), 1);
+    }
+
+    #[tokio::test]
+    async fn reads_wait_for_lagging_endpoints_to_catch_up() {
+        let memory = Arc::new(MemoryTransport::new());
+        let pool = RpcPool::from_endpoints(vec![RpcEndpoint::with_transport(memory.clone())], RpcMode::Failover);
+
+        let catch_up = {
+            let memory = memory.clone();
+            async move {
+                tokio::time::sleep(MIN_CONTEXT_SLOT_RETRY_DELAY / 2).await;
+                memory.advance_slot(9);
+            }
+        };
+        let (slot, _) = tokio::join!(get_account_at(&pool, 10), catch_up);
+        assert_eq!(slot.unwrap(), 10);
+
+        // Still behind once the retries run out
+        assert!(matches!(get_account_at(&pool, 20).await, Err(CnctdSolanaError::MinContextSlotNotReached)));
+    }
+
+    #[tokio::test]
+    async fn broadcast_returns_without_waiting_for_slow_endpoints() {
+        let pool = RpcPool::from_endpoints(vec![hanging_endpoint().await, memory_endpoint(1)], RpcMode::Failover);
+
+        let broadcast = pool.broadcast("get_slot", |transport| async move {
+            transport.get_slot_with_commitment(CommitmentConfig::confirmed()).await
+        });
+
+        assert_eq!(tokio::time::timeout(Duration::from_secs(5), broadcast).await.unwrap().unwrap(), 1);
+    }
+}
@@ -0,0 +1,364 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use solana_cluster_type::ClusterType;
+
+use crate::rpc::{
+    error::{CnctdSolanaError, Result},
+    http::HttpConfig,
+    pool::{RpcCapability, RpcEndpoint},
+    rate_limit::RateLimit,
+    RpcMode, RpcPool,
+};
+
+/// Path to a TOML or JSON registry file, read by `ProviderRegistry::from_env`
+pub const RPC_CONFIG_ENV: &str = "CNCTD_SOLANA_RPC_CONFIG";
+
+/// Inline JSON registry, read by `ProviderRegistry::from_env` when
+/// `RPC_CONFIG_ENV` is not set
+pub const RPC_PROVIDERS_ENV: &str = "CNCTD_SOLANA_RPC_PROVIDERS";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+}
+
+impl From<Cluster> for ClusterType {
+    fn from(cluster: Cluster) -> Self {
+        match cluster {
+            Cluster::Mainnet => ClusterType::MainnetBeta,
+            Cluster::Devnet => ClusterType::Devnet,
+            Cluster::Testnet => ClusterType::Testnet,
+            Cluster::Localnet => ClusterType::Development,
+        }
+    }
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// One RPC provider endpoint.
+///
+/// `url` and `ws_url` are templates: `{api_key}` is replaced with the value
+/// of the `api_key_env` variable, and any other `{NAME}` with the value of
+/// the environment variable `NAME`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProviderConfig {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    pub cluster: Cluster,
+    /// Defaults to the HTTP URL with a `ws` scheme
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// Scales the endpoint's ranking: its health score is divided by the
+    /// weight, see `RpcEndpoint::weight`
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Defaults to the known limit for the provider's domain, if any
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    /// Defaults to the capabilities known for the provider's domain
+    #[serde(default)]
+    pub capabilities: Option<Vec<RpcCapability>>,
+}
+
+/// A provider with its templates filled in from the environment
+#[derive(Debug, Clone)]
+pub struct ResolvedProvider {
+    pub name: String,
+    pub url: String,
+    pub ws_url: Option<String>,
+    pub weight: u32,
+    pub rate_limit: Option<RateLimit>,
+    pub capabilities: Option<Vec<RpcCapability>>,
+}
+
+impl ProviderConfig {
+    pub fn resolve(&self) -> Result<ResolvedProvider> {
+        let api_key = match &self.api_key_env {
+            Some(var) => Some(std::env::var(var).map_err(|_| {
+                CnctdSolanaError::Config(format!("{} is not set for provider {}", var, self.name))
+            })?),
+            None => None,
+        };
+
+        Ok(ResolvedProvider {
+            name: self.name.clone(),
+            url: fill_template(&self.url, api_key.as_deref())?,
+            ws_url: self.ws_url.as_deref().map(|ws_url| fill_template(ws_url, api_key.as_deref())).transpose()?,
+            weight: self.weight,
+            rate_limit: self.rate_limit,
+            capabilities: self.capabilities.clone(),
+        })
+    }
+}
+
+fn fill_template(template: &str, api_key: Option<&str>) -> Result<String> {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| CnctdSolanaError::Config(format!("Unclosed placeholder in {}", template)))?;
+        let name = &rest[start + 1..end];
+
+        let value = match name {
+            "api_key" => api_key
+                .map(str::to_string)
+                .ok_or_else(|| CnctdSolanaError::Config(format!("{} uses {{api_key}} without api_key_env", template)))?,
+            var => std::env::var(var)
+                .map_err(|_| CnctdSolanaError::Config(format!("{} is not set for {}", var, template)))?,
+        };
+
+        filled.push_str(&rest[..start]);
+        filled.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+/// The RPC providers available to an environment, loaded from a TOML or
+/// JSON file, or from the environment, and turned into an `RpcPool` for one
+/// cluster.
+///
+/// ```toml
+/// [[providers]]
+/// name = "helius"
+/// url = "https://mainnet.helius-rpc.com/?api-key={api_key}"
+/// api_key_env = "HELIUS_API_KEY"
+/// cluster = "mainnet"
+/// weight = 2
+/// rate_limit = { requests_per_second = 10.0, burst = 10 }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProviderRegistry {
+    #[serde(default)]
+    pub providers: Vec<ProviderConfig>,
+}
+
+impl ProviderRegistry {
+    /// The providers `RpcUrl` knows about, plus the public devnet, testnet
+    /// and local validator endpoints
+    pub fn builtin() -> Self {
+        let provider = |name: &str, url: &str, api_key_env: Option<&str>, cluster: Cluster| ProviderConfig {
+            name: name.to_string(),
+            url: url.to_string(),
+            api_key_env: api_key_env.map(str::to_string),
+            cluster,
+            ws_url: None,
+            weight: default_weight(),
+            rate_limit: None,
+            capabilities: None,
+        };
+
+        Self {
+            providers: vec![
+                provider("publicnode", "https://solana-rpc.publicnode.com", None, Cluster::Mainnet),
+                provider("quicknode", "https://{QUICKNODE_SUBDOMAIN}.solana-mainnet.quiknode.pro/{api_key}/", Some("QUICKNODE_API_KEY"), Cluster::Mainnet),
+                provider("helius", "https://rpc.helius.xyz?api-key={api_key}", Some("HELIUS_API_KEY"), Cluster::Mainnet),
+                provider("syndica", "https://solana-mainnet.api.syndica.io/api-key/{api_key}", Some("SYNDICA_API_KEY"), Cluster::Mainnet),
+                provider("chainstack", "https://solana-mainnet.core.chainstack.com/{api_key}", Some("CHAINSTACK_API_KEY"), Cluster::Mainnet),
+                provider("alchemy", "https://solana-mainnet.g.alchemy.com/v2/{api_key}", Some("ALCHEMY_API_KEY"), Cluster::Mainnet),
+                provider("drpc", "https://solana.drpc.org", None, Cluster::Mainnet),
+                provider("solana-devnet", "https://api.devnet.solana.com", None, Cluster::Devnet),
+                provider("solana-testnet", "https://api.testnet.solana.com", None, Cluster::Testnet),
+                provider("localhost", "http://127.0.0.1:8899", None, Cluster::Localnet),
+            ],
+        }
+    }
+
+    pub fn from_toml(config: &str) -> Result<Self> {
+        toml::from_str(config).map_err(|e| CnctdSolanaError::Config(format!("Invalid provider registry TOML: {}", e)))
+    }
+
+    pub fn from_json(config: &str) -> Result<Self> {
+        serde_json::from_str(config).map_err(|e| CnctdSolanaError::Config(format!("Invalid provider registry JSON: {}", e)))
+    }
+
+    /// Reads a `.toml` or `.json` registry file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path)
+            .map_err(|e| CnctdSolanaError::Config(format!("Could not read {}: {}", path.display(), e)))?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&config),
+            Some("json") => Self::from_json(&config),
+            _ => Err(CnctdSolanaError::Config(format!("{} is not a .toml or .json file", path.display()))),
+        }
+    }
+
+    /// Loads the file named by `CNCTD_SOLANA_RPC_CONFIG`, or the JSON in
+    /// `CNCTD_SOLANA_RPC_PROVIDERS`
+    pub fn from_env() -> Result<Self> {
+        if let Ok(path) = std::env::var(RPC_CONFIG_ENV) {
+            return Self::from_file(path);
+        }
+
+        match std::env::var(RPC_PROVIDERS_ENV) {
+            Ok(config) => Self::from_json(&config),
+            Err(_) => Err(CnctdSolanaError::Config(format!("Neither {} nor {} is set", RPC_CONFIG_ENV, RPC_PROVIDERS_ENV))),
+        }
+    }
+
+    /// Providers for `cluster` whose templates resolve, highest weight first.
+    /// Providers missing an environment variable are left out.
+    pub fn resolve(&self, cluster: Cluster) -> Vec<ResolvedProvider> {
+        let mut resolved: Vec<ResolvedProvider> = self.providers
+            .iter()
+            .filter(|provider| provider.cluster == cluster)
+            .filter_map(|provider| match provider.resolve() {
+                Ok(resolved) => Some(resolved),
+                Err(e) => {
+                    tracing::warn!(provider = %provider.name, error = %e, "Skipping RPC provider");
+                    None
+                }
+            })
+            .collect();
+
+        resolved.sort_by_key(|provider| std::cmp::Reverse(provider.weight));
+        resolved
+    }
+
+    pub fn pool(&self, cluster: Cluster, mode: RpcMode) -> Result<RpcPool> {
+        self.pool_with_http_config(cluster, mode, HttpConfig::default())
+    }
+
+    /// Like `pool`, with every endpoint sharing one HTTP client built from
+    /// `http_config`
+    pub fn pool_with_http_config(&self, cluster: Cluster, mode: RpcMode, http_config: HttpConfig) -> Result<RpcPool> {
+        let client = http_config.build();
+        let endpoints: Vec<RpcEndpoint> = self.resolve(cluster)
+            .into_iter()
+            .map(|provider| {
+                let mut endpoint = RpcEndpoint::with_http_client(&provider.url, client.clone()).with_weight(provider.weight);
+                if let Some(ws_url) = &provider.ws_url {
+                    endpoint = endpoint.with_ws_url(ws_url);
+                }
+                if provider.rate_limit.is_some() {
+                    endpoint = endpoint.with_rate_limit(provider.rate_limit);
+                }
+                if let Some(capabilities) = provider.capabilities {
+                    endpoint = endpoint.with_capabilities(capabilities);
+                }
+                endpoint
+            })
+            .collect();
+
+        if endpoints.is_empty() {
+            return Err(CnctdSolanaError::Config(format!("No usable RPC providers for {:?}", cluster)));
+        }
+
+        Ok(RpcPool::from_endpoints(endpoints, mode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test sets variables of its own, as tests share the environment
+
+    fn provider(name: &str, url: &str, cluster: Cluster) -> ProviderConfig {
+        ProviderConfig {
+            name: name.to_string(),
+            url: url.to_string(),
+            api_key_env: None,
+            cluster,
+            ws_url: None,
+            weight: default_weight(),
+            rate_limit: None,
+            capabilities: None,
+        }
+    }
+
+    fn config_error(result: Result<ResolvedProvider>) -> String {
+        match result {
+            Err(CnctdSolanaError::Config(message)) => message,
+            other => panic!("expected a config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fills_the_api_key_and_environment_variables() {
+        std::env::set_var("PROVIDER_TEST_FILL_KEY", "secret");
+        std::env::set_var("PROVIDER_TEST_FILL_HOST", "node-7");
+        let config = ProviderConfig {
+            api_key_env: Some("PROVIDER_TEST_FILL_KEY".to_string()),
+            ws_url: Some("wss://{PROVIDER_TEST_FILL_HOST}.example.com/ws/{api_key}".to_string()),
+            ..provider("example", "https://{PROVIDER_TEST_FILL_HOST}.example.com/{api_key}/?v=1", Cluster::Mainnet)
+        };
+
+        let resolved = config.resolve().unwrap();
+
+        assert_eq!(resolved.url, "https://node-7.example.com/secret/?v=1");
+        assert_eq!(resolved.ws_url.as_deref(), Some("wss://node-7.example.com/ws/secret"));
+        assert_eq!(provider("plain", "http://127.0.0.1:8899", Cluster::Localnet).resolve().unwrap().url, "http://127.0.0.1:8899");
+    }
+
+    #[test]
+    fn reports_missing_variables_and_bad_templates() {
+        let missing_key = ProviderConfig {
+            api_key_env: Some("PROVIDER_TEST_UNSET_KEY".to_string()),
+            ..provider("keyed", "https://example.com/{api_key}", Cluster::Mainnet)
+        };
+        assert!(config_error(missing_key.resolve()).contains("PROVIDER_TEST_UNSET_KEY is not set for provider keyed"));
+
+        let missing_var = provider("templated", "https://{PROVIDER_TEST_UNSET_HOST}.example.com", Cluster::Mainnet);
+        assert!(config_error(missing_var.resolve()).contains("PROVIDER_TEST_UNSET_HOST is not set"));
+
+        let keyless = provider("keyless", "https://example.com/{api_key}", Cluster::Mainnet);
+        assert!(config_error(keyless.resolve()).contains("without api_key_env"));
+
+        let unclosed = provider("unclosed", "https://example.com/{api_key", Cluster::Mainnet);
+        assert!(config_error(unclosed.resolve()).contains("Unclosed placeholder"));
+    }
+
+    #[test]
+    fn selects_resolvable_providers_for_the_cluster_by_weight() {
+        std::env::set_var("PROVIDER_TEST_SELECT_KEY", "secret");
+        let registry = ProviderRegistry::from_toml(r#"
+            [[providers]]
+            name = "light"
+            url = "https://light.example.com"
+            cluster = "mainnet"
+
+            [[providers]]
+            name = "heavy"
+            url = "https://heavy.example.com/{api_key}"
+            api_key_env = "PROVIDER_TEST_SELECT_KEY"
+            cluster = "mainnet"
+            weight = 3
+
+            [[providers]]
+            name = "unconfigured"
+            url = "https://unconfigured.example.com/{api_key}"
+            api_key_env = "PROVIDER_TEST_SELECT_UNSET"
+            cluster = "mainnet"
+            weight = 5
+
+            [[providers]]
+            name = "devnet"
+            url = "https://devnet.example.com"
+            cluster = "devnet"
+        "#).unwrap();
+
+        let names: Vec<String> = registry.resolve(Cluster::Mainnet).into_iter().map(|provider| provider.name).collect();
+        assert_eq!(names, vec!["heavy", "light"]);
+
+        let pool = registry.pool(Cluster::Devnet, RpcMode::Failover).unwrap();
+        assert_eq!(pool.urls(), vec!["https://devnet.example.com"]);
+
+        assert!(registry.pool(Cluster::Testnet, RpcMode::Failover).is_err());
+    }
+}
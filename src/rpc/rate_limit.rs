@@ -0,0 +1,231 @@
+use std::{sync::Mutex, time::{Duration, Instant}};
+
+use async_trait::async_trait;
+use http::Extensions;
+use reqwest::{header::RETRY_AFTER, Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::rpc::error::{CnctdSolanaError, Result};
+
+/// Backoff used after a 429 that carried no Retry-After header, doubled on
+/// each consecutive 429 from the same endpoint
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Upper bound on any single backoff, whatever the provider asks for
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Requests an endpoint accepts, built by `per_second` or deserialized,
+/// either of which rejects a rate that is not positive: a bucket that never
+/// refills would hold every request forever.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "RateLimitConfig")]
+pub struct RateLimit {
+    requests_per_second: f64,
+    burst: u32,
+}
+
+#[derive(Deserialize)]
+struct RateLimitConfig {
+    requests_per_second: f64,
+    burst: Option<u32>,
+}
+
+impl TryFrom<RateLimitConfig> for RateLimit {
+    type Error = CnctdSolanaError;
+
+    fn try_from(config: RateLimitConfig) -> Result<Self> {
+        let limit = Self::per_second(config.requests_per_second)?;
+
+        Ok(match config.burst {
+            Some(burst) => limit.with_burst(burst),
+            None => limit,
+        })
+    }
+}
+
+impl RateLimit {
+    pub fn per_second(requests_per_second: f64) -> Result<Self> {
+        if !requests_per_second.is_finite() || requests_per_second <= 0.0 {
+            return Err(CnctdSolanaError::Config(format!("requests_per_second must be positive, got {}", requests_per_second)));
+        }
+
+        Ok(Self {
+            requests_per_second,
+            burst: requests_per_second.ceil().max(1.0) as u32,
+        })
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+}
+
+#[derive(Debug)]
+struct BucketState {
+    limit: Option<RateLimit>,
+    tokens: f64,
+    last_refill: Instant,
+    backoff_until: Option<Instant>,
+    consecutive_rate_limits: u32,
+}
+
+/// Token bucket guarding a single endpoint, plus the backoff window set when
+/// the endpoint answers with HTTP 429.
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<BucketState>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: Option<RateLimit>) -> Self {
+        Self {
+            state: Mutex::new(BucketState {
+                limit,
+                tokens: limit.map(|limit| limit.burst as f64).unwrap_or_default(),
+                last_refill: Instant::now(),
+                backoff_until: None,
+                consecutive_rate_limits: 0,
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> Option<RateLimit> {
+        self.state.lock().unwrap().limit
+    }
+
+    pub fn set_limit(&self, limit: Option<RateLimit>) {
+        let mut state = self.state.lock().unwrap();
+        state.limit = limit;
+        state.tokens = limit.map(|limit| limit.burst as f64).unwrap_or_default();
+        state.last_refill = Instant::now();
+    }
+
+    /// Waits until a request may be sent, then takes a token.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+
+                match state.backoff_until {
+                    Some(until) if until > now => until - now,
+                    _ => match state.limit {
+                        None => return,
+                        Some(limit) => {
+                            let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                            state.tokens = (state.tokens + elapsed * limit.requests_per_second).min(limit.burst as f64);
+                            state.last_refill = now;
+
+                            if state.tokens >= 1.0 {
+                                state.tokens -= 1.0;
+                                return;
+                            }
+
+                            Duration::from_secs_f64((1.0 - state.tokens) / limit.requests_per_second)
+                        }
+                    },
+                }
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Records a 429 and pauses the endpoint for `retry_after`, or an
+    /// exponential backoff when the provider did not say.
+    pub fn back_off(&self, retry_after: Option<Duration>) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let exponential = BASE_BACKOFF.saturating_mul(2u32.saturating_pow(state.consecutive_rate_limits.min(16)));
+        let backoff = retry_after.unwrap_or(exponential).min(MAX_BACKOFF);
+
+        state.consecutive_rate_limits += 1;
+        state.backoff_until = Some(Instant::now() + backoff);
+        backoff
+    }
+
+    pub fn record_success(&self) {
+        self.state.lock().unwrap().consecutive_rate_limits = 0;
+    }
+
+    /// Time left before the endpoint may be called again after a 429
+    pub fn backoff_remaining(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.backoff_until.filter(|until| *until > now).map(|until| until - now)
+    }
+}
+
+/// Raised by `RetryAfterMiddleware` in place of an HTTP 429 response.
+#[derive(Debug, Error)]
+#[error("HTTP 429 Too Many Requests")]
+pub struct RateLimitedResponse {
+    pub retry_after: Option<Duration>,
+}
+
+/// Turns HTTP 429 responses into `RateLimitedResponse` errors carrying the
+/// Retry-After delay, so that backoff is decided by the pool rather than by
+/// `HttpSender`'s fixed internal retries.
+pub struct RetryAfterMiddleware;
+
+#[async_trait]
+impl Middleware for RetryAfterMiddleware {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let response = next.run(request, extensions).await?;
+
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+
+        let retry_after = response.headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        Err(reqwest_middleware::Error::middleware(RateLimitedResponse { retry_after }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::provider::ProviderRegistry;
+
+    #[test]
+    fn rejects_rates_that_are_not_positive() {
+        assert!(RateLimit::per_second(0.0).is_err());
+        assert!(RateLimit::per_second(-1.0).is_err());
+        assert!(RateLimit::per_second(f64::NAN).is_err());
+        assert_eq!(RateLimit::per_second(2.5).unwrap().burst(), 3);
+    }
+
+    #[test]
+    fn registry_rejects_rates_that_are_not_positive() {
+        let registry = |rate_limit: &str| ProviderRegistry::from_toml(&format!(
+            "[[providers]]\nname = \"local\"\nurl = \"http://127.0.0.1:8899\"\ncluster = \"localnet\"\nrate_limit = {}\n",
+            rate_limit,
+        ));
+
+        assert!(matches!(registry("{ requests_per_second = 0.0, burst = 10 }"), Err(CnctdSolanaError::Config(_))));
+        assert!(matches!(registry("{ requests_per_second = -5.0 }"), Err(CnctdSolanaError::Config(_))));
+
+        let limit = registry("{ requests_per_second = 10.0, burst = 20 }").unwrap().providers[0].rate_limit.unwrap();
+        assert_eq!((limit.requests_per_second(), limit.burst()), (10.0, 20));
+    }
+}
@@ -0,0 +1,208 @@
+use std::{sync::Arc, time::Duration};
+
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    signature::{Keypair, Signature},
+    transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::rpc::{
+    client::CnctdSolana,
+    error::{CnctdSolanaError, Result},
+    ConfirmationOutcome,
+};
+
+/// How often a transaction is sent again while it has not landed. Leaders
+/// change every four slots, about 1.6s.
+pub const DEFAULT_REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+
+impl CnctdSolana {
+    pub fn with_rebroadcast_interval(mut self, interval: Duration) -> Self {
+        self.rebroadcast_interval = interval;
+        self
+    }
+
+    /// Like `sign_and_confirm_transaction`, but keeps sending the transaction
+    /// to every endpoint until it lands or expires, see `rebroadcast_until_confirmed`
+    pub async fn sign_and_rebroadcast_transaction(
+        &self,
+        transaction: &Transaction,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<Signature> {
+        self.sign_and_rebroadcast_transaction_with_commitment(transaction, additional_signers, CommitmentConfig::confirmed()).await
+    }
+
+    /// Takes the blockhash at the instance commitment and waits for `commitment`
+    pub async fn sign_and_rebroadcast_transaction_with_commitment(
+        &self,
+        transaction: &Transaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let (recent_blockhash, last_valid_block_height) = self.latest_blockhash(self.commitment).await?;
+
+        let signer_keypair = self.signer_keypair.as_ref()
+            .ok_or(CnctdSolanaError::MissingSigner)?;
+        let mut signers = vec![signer_keypair];
+        signers.extend(additional_signers.unwrap_or_default());
+
+        let mut signed_transaction = transaction.clone();
+        signed_transaction.try_sign(&signers, recent_blockhash)
+            .map_err(|e| CnctdSolanaError::InvalidInput(format!("Could not sign transaction: {}", e)))?;
+
+        let signed_transaction = VersionedTransaction::from(signed_transaction);
+        let signature = signed_transaction.signatures[0];
+
+        match self.rebroadcast_until_confirmed(&signed_transaction, last_valid_block_height, commitment).await? {
+            ConfirmationOutcome::Confirmed(..) => Ok(signature),
+            ConfirmationOutcome::Failed(err) => Err(err.into()),
+            ConfirmationOutcome::Expired => Err(CnctdSolanaError::BlockhashExpired),
+        }
+    }
+
+    /// Like `sign_and_rebroadcast_transaction`, for a versioned transaction
+    pub async fn sign_and_rebroadcast_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<Signature> {
+        self.sign_and_rebroadcast_versioned_transaction_with_commitment(transaction, additional_signers, CommitmentConfig::confirmed()).await
+    }
+
+    pub async fn sign_and_rebroadcast_versioned_transaction_with_commitment(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let (recent_blockhash, last_valid_block_height) = self.latest_blockhash(self.commitment).await?;
+        let signed_transaction = self.sign_versioned_transaction(transaction, additional_signers, recent_blockhash)?;
+        let signature = signed_transaction.signatures[0];
+
+        match self.rebroadcast_until_confirmed(&signed_transaction, last_valid_block_height, commitment).await? {
+            ConfirmationOutcome::Confirmed(..) => Ok(signature),
+            ConfirmationOutcome::Failed(err) => Err(err.into()),
+            ConfirmationOutcome::Expired => Err(CnctdSolanaError::BlockhashExpired),
+        }
+    }
+
+    /// Sends a signed transaction to every endpoint, without preflight and
+    /// with node retries off, then sends the same bytes again every
+    /// `rebroadcast_interval` until `confirm_transaction` reports an outcome.
+    ///
+    /// Nodes drop transactions freely under load, so rather than trusting one
+    /// send to be forwarded to the leader, this keeps offering it to each
+    /// new one. Resending is safe: a signature can only land once.
+    #[tracing::instrument(level = "debug", skip_all, fields(signature = %transaction.signatures[0]))]
+    pub async fn rebroadcast_until_confirmed(
+        &self,
+        transaction: &VersionedTransaction,
+        last_valid_block_height: u64,
+        commitment: CommitmentConfig,
+    ) -> Result<ConfirmationOutcome> {
+        let signature = self.broadcast_transaction(transaction).await?;
+
+        let rebroadcast = async {
+            let mut interval = tokio::time::interval(self.rebroadcast_interval);
+            // The first tick completes immediately, and the first send is done
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.broadcast_transaction(transaction).await {
+                    tracing::debug!(error = %e, "Rebroadcast failed");
+                }
+            }
+        };
+
+        tokio::select! {
+            outcome = self.confirm_transaction(&signature, last_valid_block_height, commitment) => outcome,
+            _ = rebroadcast => unreachable!("rebroadcasting only stops once confirmation returns"),
+        }
+    }
+
+    async fn broadcast_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        let config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            max_retries: Some(0),
+            ..RpcSendTransactionConfig::default()
+        };
+
+        let transaction = Arc::new(transaction.clone());
+        self.pool.broadcast("send_transaction", move |transport| {
+            let transaction = transaction.clone();
+            async move { transport.send_versioned_transaction_with_config(&transaction, config).await }
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{pubkey::Pubkey, signer::Signer};
+    use solana_system_interface::instruction as system_instruction;
+
+    use super::*;
+    use crate::rpc::MemoryTransport;
+
+    fn client(memory: &Arc<MemoryTransport>) -> CnctdSolana {
+        let mut client = CnctdSolana::with_transport(memory.clone())
+            .unwrap()
+            .with_rebroadcast_interval(Duration::from_millis(10));
+        client.signer_keypair = Some(Keypair::new());
+        client
+    }
+
+    fn transfer(client: &CnctdSolana) -> Transaction {
+        let payer = client.signer_keypair.as_ref().unwrap().pubkey();
+
+        Transaction::new_with_payer(&[system_instruction::transfer(&payer, &Pubkey::new_unique(), 1)], Some(&payer))
+    }
+
+    #[tokio::test]
+    async fn reports_the_signature_once_a_resend_lands() {
+        let memory = Arc::new(MemoryTransport::new());
+        memory.drop_sends(2);
+        let client = client(&memory);
+
+        let signature = client.sign_and_rebroadcast_transaction(&transfer(&client), None).await.unwrap();
+
+        let sent = memory.sent_versioned_transactions();
+        assert!(sent.len() >= 3);
+        assert!(sent.iter().all(|transaction| transaction.signatures[0] == signature));
+    }
+
+    #[tokio::test]
+    async fn keeps_confirming_through_poll_errors() {
+        let memory = Arc::new(MemoryTransport::new());
+        memory.fail_status_polls(2);
+        let client = client(&memory);
+
+        let signature = client.sign_and_rebroadcast_transaction(&transfer(&client), None).await.unwrap();
+
+        assert_eq!(memory.sent_versioned_transactions()[0].signatures[0], signature);
+    }
+
+    #[tokio::test]
+    async fn stops_rebroadcasting_once_the_blockhash_expires() {
+        let memory = Arc::new(MemoryTransport::new());
+        memory.drop_sends(usize::MAX);
+        let client = client(&memory);
+
+        let (blockhash, _) = client.latest_blockhash(client.commitment).await.unwrap();
+        let mut transaction = transfer(&client);
+        transaction.sign(&[client.signer_keypair.as_ref().unwrap()], blockhash);
+        let last_valid_block_height = 1;
+        memory.advance_slot(1);
+
+        let outcome = client
+            .rebroadcast_until_confirmed(&transaction.into(), last_valid_block_height, CommitmentConfig::confirmed())
+            .await
+            .unwrap();
+        assert_eq!(outcome, ConfirmationOutcome::Expired);
+
+        let sends = memory.sent_versioned_transactions().len();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(memory.sent_versioned_transactions().len(), sends);
+    }
+}
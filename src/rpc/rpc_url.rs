@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::rpc::{pool::RpcCapability, rate_limit::RateLimit, DomainExtractor};
+
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RpcUrl;
@@ -29,8 +31,9 @@ impl RpcUrl {
 
     pub fn quicknode() -> anyhow::Result<String> {
         let api_key = std::env::var("QUICKNODE_API_KEY")?;
+        let subdomain = std::env::var("QUICKNODE_SUBDOMAIN")?;
 
-        Ok(format!("https://fittest-bold-card.solana-mainnet.quiknode.pro/{}/", api_key))
+        Ok(format!("https://{}.solana-mainnet.quiknode.pro/{}/", subdomain, api_key))
     }
 
     pub fn alchemy() -> anyhow::Result<String> {
@@ -68,19 +71,19 @@ impl RpcUrl {
         urls.push(Self::publicnode());
         match Self::syndica() {
             Ok(url) => urls.push(url),
-            Err(e) => eprintln!("Error getting syndica url: {:?}", e),
+            Err(e) => tracing::warn!(error = ?e, "Error getting syndica url"),
         }
         match Self::chainstack() {
             Ok(url) => urls.push(url),
-            Err(e) => eprintln!("Error getting chainstack url: {:?}", e),
+            Err(e) => tracing::warn!(error = ?e, "Error getting chainstack url"),
         }
         match Self::quicknode() {
             Ok(url) => urls.push(url),
-            Err(e) => eprintln!("Error getting quicknode url: {:?}", e),
+            Err(e) => tracing::warn!(error = ?e, "Error getting quicknode url"),
         }
         match Self::helius() {
             Ok(url) => urls.push(url),
-            Err(e) => eprintln!("Error getting helius url: {:?}", e),
+            Err(e) => tracing::warn!(error = ?e, "Error getting helius url"),
         }
         urls
     }
@@ -90,15 +93,15 @@ impl RpcUrl {
         urls.push(Self::publicnode());
         match Self::quicknode() {
             Ok(url) => urls.push(url),
-            Err(e) => eprintln!("Error getting quicknode url: {:?}", e),
+            Err(e) => tracing::warn!(error = ?e, "Error getting quicknode url"),
         }
         match Self::helius() {
             Ok(url) => urls.push(url),
-            Err(e) => eprintln!("Error getting helius url: {:?}", e),
+            Err(e) => tracing::warn!(error = ?e, "Error getting helius url"),
         }
         match Self::syndica() {
             Ok(url) => urls.push(url),
-            Err(e) => eprintln!("Error getting syndica url: {:?}", e),
+            Err(e) => tracing::warn!(error = ?e, "Error getting syndica url"),
         }
         // match Self::chainstack() {
         //     Ok(url) => urls.push(url),
@@ -112,6 +115,45 @@ impl RpcUrl {
     }
 }
 
+/// Conservative request rates (per second) for each provider's entry-level
+/// plan, keyed by domain suffix. The public Solana endpoints allow 100
+/// requests per 10 seconds.
+const PROVIDER_RATE_LIMITS: [(&str, f64); 9] = [
+    ("helius.xyz", 10.0),
+    ("helius-rpc.com", 10.0),
+    ("quiknode.pro", 15.0),
+    ("syndica.io", 50.0),
+    ("chainstack.com", 25.0),
+    ("alchemy.com", 25.0),
+    ("publicnode.com", 10.0),
+    ("drpc.org", 20.0),
+    ("solana.com", 10.0),
+];
+
+impl RpcUrl {
+    /// Default limit for a known provider, used unless the pool is given an
+    /// explicit limit for the endpoint.
+    pub fn default_rate_limit(url: &str) -> Option<RateLimit> {
+        let domain = url.domain();
+
+        PROVIDER_RATE_LIMITS
+            .iter()
+            .find(|(suffix, _)| domain.ends_with(suffix))
+            .and_then(|(_, requests_per_second)| RateLimit::per_second(*requests_per_second).ok())
+    }
+
+    /// Provider APIs known to be served at `url`
+    pub fn default_capabilities(url: &str) -> Vec<RpcCapability> {
+        let domain = url.domain();
+
+        if domain.ends_with("helius.xyz") || domain.ends_with("helius-rpc.com") {
+            vec![RpcCapability::PriorityFeeEstimate, RpcCapability::DigitalAssetStandard]
+        } else {
+            vec![]
+        }
+    }
+}
+
 pub trait RpcUrlExt {
     fn to_ws(&self) -> String;
 }
@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use solana_commitment_config::{CommitmentConfig, CommitmentLevel};
+
+/// Highest slot seen at each commitment level, from response contexts and
+/// confirmations, so reads can ask for state at least that new.
+///
+/// A slot seen at a commitment has also been reached at every weaker one,
+/// but not at stronger ones: a read at `finalized` may legitimately be
+/// behind a transaction that has only just confirmed.
+#[derive(Debug, Default)]
+pub struct SlotTracker {
+    processed: AtomicU64,
+    confirmed: AtomicU64,
+    finalized: AtomicU64,
+}
+
+impl SlotTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn level(&self, commitment: CommitmentLevel) -> &AtomicU64 {
+        match commitment {
+            CommitmentLevel::Processed => &self.processed,
+            CommitmentLevel::Confirmed => &self.confirmed,
+            CommitmentLevel::Finalized => &self.finalized,
+        }
+    }
+
+    pub fn observe(&self, commitment: CommitmentConfig, slot: u64) {
+        self.level(commitment.commitment).fetch_max(slot, Ordering::Relaxed);
+    }
+
+    /// Highest slot known to be reached at `commitment`
+    pub fn highest(&self, commitment: CommitmentConfig) -> u64 {
+        let at_least = |level: CommitmentLevel| self.level(level).load(Ordering::Relaxed);
+
+        match commitment.commitment {
+            CommitmentLevel::Processed => at_least(CommitmentLevel::Processed)
+                .max(at_least(CommitmentLevel::Confirmed))
+                .max(at_least(CommitmentLevel::Finalized)),
+            CommitmentLevel::Confirmed => at_least(CommitmentLevel::Confirmed).max(at_least(CommitmentLevel::Finalized)),
+            CommitmentLevel::Finalized => at_least(CommitmentLevel::Finalized),
+        }
+    }
+
+    /// `min_context_slot` for a read at `commitment`, combined with any
+    /// minimum the caller already has; `None` until a slot has been seen
+    pub fn min_context_slot(&self, commitment: CommitmentConfig, min_context_slot: Option<u64>) -> Option<u64> {
+        Some(self.highest(commitment))
+            .filter(|slot| *slot > 0)
+            .max(min_context_slot)
+    }
+}
@@ -0,0 +1,85 @@
+use std::{future::Future, pin::Pin, task::{Context, Poll}, time::Duration};
+
+use futures::Stream;
+use solana_client::nonblocking::pubsub_client::{PubsubClient, PubsubClientError};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::rpc::DomainExtractor;
+
+/// Delay before the first reconnect attempt, doubled on each failed attempt
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound on the delay between reconnect attempts
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// A websocket subscription kept alive by a background task that reconnects
+/// and resubscribes whenever the socket drops, moving on to the next
+/// endpoint each time. Dropping the stream ends the subscription.
+pub struct Subscription<T> {
+    receiver: mpsc::UnboundedReceiver<T>,
+    task: JoinHandle<()>,
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    /// Connects to each of `ws_urls` in turn and runs `session` on the
+    /// connection until it returns. `session` should subscribe, forward
+    /// notifications to the sender and return `Ok` once the notification
+    /// stream ends; the flag it is given is `true` when resubscribing after
+    /// a dropped connection.
+    pub(crate) fn spawn<F, Fut>(method: &'static str, ws_urls: Vec<String>, session: F) -> Self
+    where
+        F: Fn(PubsubClient, mpsc::UnboundedSender<T>, bool) -> Fut + Send + 'static,
+        Fut: Future<Output = std::result::Result<(), PubsubClientError>> + Send,
+    {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(async move {
+            let mut failures = 0u32;
+            let mut resubscribe = false;
+
+            for ws_url in ws_urls.iter().cycle() {
+                let result = match PubsubClient::new(ws_url).await {
+                    Ok(client) => session(client, sender.clone(), resubscribe).await,
+                    Err(e) => Err(e),
+                };
+
+                if sender.is_closed() {
+                    return;
+                }
+
+                match result {
+                    Ok(()) => {
+                        failures = 0;
+                        resubscribe = true;
+                        tracing::warn!(method, domain = %ws_url.domain(), "Subscription dropped, reconnecting");
+                    }
+                    Err(e) => {
+                        tracing::warn!(method, domain = %ws_url.domain(), error = %e, "Subscription failed");
+                    }
+                }
+
+                let delay = RECONNECT_BASE_DELAY
+                    .saturating_mul(2u32.saturating_pow(failures.min(16)))
+                    .min(RECONNECT_MAX_DELAY);
+                failures += 1;
+                tokio::time::sleep(delay).await;
+            }
+        });
+
+        Self { receiver, task }
+    }
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
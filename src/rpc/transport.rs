@@ -0,0 +1,371 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_account_decoder_client_types::UiAccount;
+use solana_client::{
+    client_error::Result as ClientResult,
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_request::{RpcError, RpcRequest},
+    rpc_response::{Response, RpcPrioritizationFee, RpcSimulateTransactionResult},
+};
+use solana_rpc_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    account::Account, hash::Hash, pubkey::Pubkey, signature::Signature,
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_transaction_status::TransactionStatus;
+
+/// The RPC calls `CnctdSolana` depends on.
+///
+/// `RpcClient` implements this against a live node; `MemoryTransport` keeps
+/// everything in process so client code can be exercised without a cluster.
+#[async_trait]
+pub trait RpcTransport: Send + Sync {
+    fn url(&self) -> String;
+
+    /// The `RpcClient` behind this transport, if there is one
+    fn rpc_client(&self) -> Option<&RpcClient> {
+        None
+    }
+
+    async fn get_account_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Option<Account>>>;
+
+    /// Accounts in the same order as `pubkeys`; callers keep batches to 100
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Vec<Option<Account>>>>;
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>>;
+
+    /// Latest blockhash and the last block height at which it is valid
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)>;
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64>;
+
+    async fn get_block_height_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64>;
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64>;
+
+    /// Lowest fee paid to land in each recent slot by transactions writing
+    /// to all of `addresses`
+    async fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> ClientResult<Vec<RpcPrioritizationFee>>;
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>>;
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    async fn simulate_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>>;
+
+    async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Response<Vec<Option<TransactionStatus>>>>;
+
+    /// Raw JSON-RPC call for provider-specific methods, returning the
+    /// `result` field. `params` may be an array or, where the transport
+    /// supports it, an object of named params.
+    async fn send_json_request(&self, method: &'static str, params: Value) -> ClientResult<Value>;
+}
+
+#[async_trait]
+impl RpcTransport for RpcClient {
+    fn url(&self) -> String {
+        RpcClient::url(self)
+    }
+
+    fn rpc_client(&self) -> Option<&RpcClient> {
+        Some(self)
+    }
+
+    async fn get_account_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Option<Account>>> {
+        get_account_info(self, pubkey, config).await
+    }
+
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Vec<Option<Account>>>> {
+        RpcClient::get_multiple_accounts_with_config(self, pubkeys, config).await
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        RpcClient::get_program_accounts_with_config(self, program_id, config).await
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        RpcClient::get_latest_blockhash_with_commitment(self, commitment).await
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        RpcClient::get_slot_with_commitment(self, commitment).await
+    }
+
+    async fn get_block_height_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        RpcClient::get_block_height_with_commitment(self, commitment).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        RpcClient::get_minimum_balance_for_rent_exemption(self, data_len).await
+    }
+
+    async fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        RpcClient::get_recent_prioritization_fees(self, addresses).await
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        RpcClient::simulate_transaction_with_config(self, transaction, config).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_transaction_with_config(self, transaction, config).await
+    }
+
+    async fn simulate_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        RpcClient::simulate_transaction_with_config(self, transaction, config).await
+    }
+
+    async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_transaction_with_config(self, transaction, config).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Response<Vec<Option<TransactionStatus>>>> {
+        RpcClient::get_signature_statuses(self, signatures).await
+    }
+
+    async fn send_json_request(&self, method: &'static str, params: Value) -> ClientResult<Value> {
+        // `RpcClient::send` only accepts positional params
+        if !(params.is_array() || params.is_null()) {
+            return Err(RpcError::RpcRequestError(format!("{} needs named params, which RpcClient cannot send", method)).into());
+        }
+
+        RpcClient::send(self, RpcRequest::Custom { method }, params).await
+    }
+}
+
+/// `RpcSender` shared between an `RpcClient` and raw JSON-RPC calls
+#[derive(Clone)]
+struct SharedSender(Arc<dyn RpcSender + Send + Sync>);
+
+#[async_trait]
+impl RpcSender for SharedSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        self.0.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.0.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.0.url()
+    }
+}
+
+/// `getAccountInfo` sent as is. `RpcClient::get_account_with_config` folds
+/// every failure, connection errors included, into a `ForUser`
+/// "AccountNotFound" message that cannot be told apart or retried.
+async fn get_account_info(
+    client: &RpcClient,
+    pubkey: &Pubkey,
+    config: RpcAccountInfoConfig,
+) -> ClientResult<Response<Option<Account>>> {
+    let response: Response<Option<UiAccount>> = client
+        .send(RpcRequest::GetAccountInfo, json!([pubkey.to_string(), config]))
+        .await?;
+
+    Ok(Response {
+        context: response.context,
+        value: response.value.and_then(|account| account.decode()),
+    })
+}
+
+/// Transport over an HTTP `RpcSender` that, unlike a bare `RpcClient`, can
+/// send raw requests with named params (as DAS methods expect).
+pub struct HttpTransport {
+    client: RpcClient,
+    sender: SharedSender,
+}
+
+impl HttpTransport {
+    pub fn new(sender: impl RpcSender + Send + Sync + 'static, config: RpcClientConfig) -> Self {
+        let sender = SharedSender(Arc::new(sender));
+
+        Self {
+            client: RpcClient::new_sender(sender.clone(), config),
+            sender,
+        }
+    }
+
+    pub fn client(&self) -> &RpcClient {
+        &self.client
+    }
+}
+
+#[async_trait]
+impl RpcTransport for HttpTransport {
+    fn url(&self) -> String {
+        self.client.url()
+    }
+
+    fn rpc_client(&self) -> Option<&RpcClient> {
+        Some(&self.client)
+    }
+
+    async fn get_account_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Option<Account>>> {
+        get_account_info(&self.client, pubkey, config).await
+    }
+
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> ClientResult<Response<Vec<Option<Account>>>> {
+        self.client.get_multiple_accounts_with_config(pubkeys, config).await
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.client.get_program_accounts_with_config(program_id, config).await
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        self.client.get_latest_blockhash_with_commitment(commitment).await
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.client.get_slot_with_commitment(commitment).await
+    }
+
+    async fn get_block_height_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.client.get_block_height_with_commitment(commitment).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.client.get_minimum_balance_for_rent_exemption(data_len).await
+    }
+
+    async fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.client.get_recent_prioritization_fees(addresses).await
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        self.client.simulate_transaction_with_config(transaction, config).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.client.send_transaction_with_config(transaction, config).await
+    }
+
+    async fn simulate_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        self.client.simulate_transaction_with_config(transaction, config).await
+    }
+
+    async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.client.send_transaction_with_config(transaction, config).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Response<Vec<Option<TransactionStatus>>>> {
+        self.client.get_signature_statuses(signatures).await
+    }
+
+    async fn send_json_request(&self, method: &'static str, params: Value) -> ClientResult<Value> {
+        let request = RpcRequest::Custom { method };
+
+        self.sender.send(request, params).await.map_err(|e| e.into_with_request(request))
+    }
+}
@@ -0,0 +1,278 @@
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_client::{
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+
+use crate::rpc::{
+    client::{CnctdSolana, TransactionExt},
+    error::{CnctdSolanaError, Result},
+    ConfirmationOutcome,
+};
+
+impl TransactionExt for VersionedTransaction {
+    fn to_base64_string(&self) -> Result<String> {
+        let serialized_tx = bincode::serialize(self)
+            .map_err(|e| CnctdSolanaError::Serialization(e.to_string()))?;
+
+        Ok(base64::engine::general_purpose::STANDARD.encode(serialized_tx))
+    }
+}
+
+impl CnctdSolana {
+    /// Compiles `instructions` into an unsigned v0 transaction, moving every
+    /// account found in `lookup_tables` out of the message. The blockhash is
+    /// set when the transaction is signed.
+    pub fn compile_versioned_transaction(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction> {
+        let message = v0::Message::try_compile(payer, instructions, lookup_tables, Hash::default())
+            .map_err(|e| CnctdSolanaError::InvalidInput(format!("Could not compile v0 message: {}", e)))?;
+        let message = VersionedMessage::V0(message);
+
+        Ok(VersionedTransaction {
+            signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+            message,
+        })
+    }
+
+    /// Like `create_unsigned_transaction`, compiled as a v0 transaction
+    /// against `lookup_tables`
+    pub async fn create_unsigned_versioned_transaction<T: BorshSerialize>(
+        &self,
+        program_id: Pubkey,
+        instruction_name: &str,
+        instruction_data: T,
+        accounts: Vec<AccountMeta>,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction> {
+        let payer_pubkey = accounts
+            .iter()
+            .find(|meta| meta.is_signer && meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .ok_or_else(|| CnctdSolanaError::InvalidInput("No writable signer found to act as payer".to_string()))?;
+
+        let instruction = self.create_instruction(program_id, instruction_name, instruction_data, accounts).await?;
+        let instructions = match &self.compute_budget {
+            Some(config) => self.apply_compute_budget(&payer_pubkey, &[instruction], lookup_tables, config).await?,
+            None => vec![instruction],
+        };
+
+        Self::compile_versioned_transaction(&payer_pubkey, &instructions, lookup_tables)
+    }
+
+    /// Sets `recent_blockhash` and signs with the instance signer and
+    /// `additional_signers`, which must cover every required signature
+    pub(crate) fn sign_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+        recent_blockhash: Hash,
+    ) -> Result<VersionedTransaction> {
+        let signer_keypair = self.signer_keypair.as_ref()
+            .ok_or(CnctdSolanaError::MissingSigner)?;
+
+        let mut message = transaction.message.clone();
+        message.set_recent_blockhash(recent_blockhash);
+
+        // `try_new` wants each required signer exactly once
+        let mut signers = vec![signer_keypair];
+        for signer in additional_signers.unwrap_or_default() {
+            if !signers.iter().any(|existing| existing.pubkey() == signer.pubkey()) {
+                signers.push(signer);
+            }
+        }
+
+        VersionedTransaction::try_new(message, &signers)
+            .map_err(|e| CnctdSolanaError::InvalidInput(format!("Could not sign transaction: {}", e)))
+    }
+
+    /// Signs, sends and waits for `confirmed`, whatever the instance commitment
+    pub async fn sign_and_confirm_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<Signature> {
+        self.sign_and_confirm_versioned_transaction_with_commitment(transaction, additional_signers, CommitmentConfig::confirmed()).await
+    }
+
+    /// Sends at the instance commitment and waits for `commitment`
+    pub async fn sign_and_confirm_versioned_transaction_with_commitment(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let (signature, last_valid_block_height) = self.sign_and_send_versioned_transaction(transaction, additional_signers).await?;
+
+        match self.confirm_transaction(&signature, last_valid_block_height, commitment).await? {
+            ConfirmationOutcome::Confirmed(..) => Ok(signature),
+            ConfirmationOutcome::Failed(err) => Err(err.into()),
+            ConfirmationOutcome::Expired => Err(CnctdSolanaError::BlockhashExpired),
+        }
+    }
+
+    /// Like `sign_and_send_transaction`, for a versioned transaction
+    pub async fn sign_and_send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<(Signature, u64)> {
+        self.sign_and_send_versioned_transaction_with_commitment(transaction, additional_signers, self.commitment).await
+    }
+
+    /// Takes the blockhash and runs preflight at `commitment`
+    #[tracing::instrument(level = "debug", skip_all, fields(signature = tracing::field::Empty))]
+    pub async fn sign_and_send_versioned_transaction_with_commitment(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<(Signature, u64)> {
+        let (recent_blockhash, last_valid_block_height) = self.latest_blockhash(commitment).await?;
+        let signed_transaction = self.sign_versioned_transaction(transaction, additional_signers, recent_blockhash)?;
+
+        let config = RpcSendTransactionConfig {
+            preflight_commitment: Some(commitment.commitment),
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
+            ..RpcSendTransactionConfig::default()
+        };
+        let signed_transaction = &signed_transaction;
+        let signature = self.pool.call("send_transaction", |transport| async move {
+            transport.send_versioned_transaction_with_config(signed_transaction, config).await
+        }).await?;
+        tracing::Span::current().record("signature", tracing::field::display(&signature));
+
+        Ok((signature, last_valid_block_height))
+    }
+
+    /// Simulates at `confirmed`, whatever the instance commitment
+    pub async fn simulate_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+    ) -> Result<RpcSimulateTransactionResult> {
+        self.simulate_versioned_transaction_with_commitment(transaction, additional_signers, CommitmentConfig::confirmed()).await
+    }
+
+    pub async fn simulate_versioned_transaction_with_commitment(
+        &self,
+        transaction: &VersionedTransaction,
+        additional_signers: Option<&[&Keypair]>,
+        commitment: CommitmentConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        let (recent_blockhash, _last_valid_block_height) = self.latest_blockhash(commitment).await?;
+        let signed_transaction = self.sign_versioned_transaction(transaction, additional_signers, recent_blockhash)?;
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(commitment),
+            accounts: None,
+            encoding: None,
+            min_context_slot: self.slot_tracker.min_context_slot(commitment, None),
+            inner_instructions: true,
+        };
+
+        let signed_transaction = &signed_transaction;
+        let simulation_result = self.pool.call("simulate_transaction", |transport| {
+            let config = config.clone();
+            async move { transport.simulate_versioned_transaction_with_config(signed_transaction, config).await }
+        }).await?;
+        self.slot_tracker.observe(commitment, simulation_result.context.slot);
+
+        Ok(simulation_result.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use solana_system_interface::instruction as system_instruction;
+
+    use super::*;
+    use crate::rpc::MemoryTransport;
+
+    #[test]
+    fn compiles_table_addresses_into_lookups() {
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let read_only = Pubkey::new_unique();
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![Pubkey::new_unique(), read_only, recipient],
+        };
+        let instructions = [
+            system_instruction::transfer(&payer, &recipient, 1),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![AccountMeta::new_readonly(read_only, false)]),
+        ];
+
+        let transaction = CnctdSolana::compile_versioned_transaction(&payer, &instructions, std::slice::from_ref(&table)).unwrap();
+
+        let VersionedMessage::V0(message) = &transaction.message else { panic!("compiled a v0 message") };
+        assert_eq!(transaction.signatures.len(), 1);
+        assert!(!message.account_keys.contains(&recipient));
+        assert!(!message.account_keys.contains(&read_only));
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.address_table_lookups[0].account_key, table.key);
+        assert_eq!(message.address_table_lookups[0].writable_indexes, vec![2]);
+        assert_eq!(message.address_table_lookups[0].readonly_indexes, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn signs_and_sends_with_the_latest_blockhash() {
+        let memory = Arc::new(MemoryTransport::new());
+        let mut client = CnctdSolana::with_transport(memory.clone()).unwrap();
+        client.signer_keypair = Some(Keypair::new());
+        let payer = client.signer_keypair.as_ref().unwrap().pubkey();
+        let recipient = Pubkey::new_unique();
+        let table = AddressLookupTableAccount { key: Pubkey::new_unique(), addresses: vec![recipient] };
+
+        let transaction = CnctdSolana::compile_versioned_transaction(
+            &payer,
+            &[system_instruction::transfer(&payer, &recipient, 1)],
+            &[table],
+        ).unwrap();
+        let signature = client.sign_and_confirm_versioned_transaction(&transaction, None).await.unwrap();
+
+        let sent = &memory.sent_versioned_transactions()[0];
+        let (blockhash, _) = client.latest_blockhash(client.commitment).await.unwrap();
+        assert_eq!(sent.signatures[0], signature);
+        assert_eq!(*sent.message.recent_blockhash(), blockhash);
+        assert!(sent.verify_with_results().iter().all(|verified| *verified));
+    }
+
+    #[test]
+    fn signing_needs_every_required_signer() {
+        let mut client = CnctdSolana::with_transport(Arc::new(MemoryTransport::new())).unwrap();
+        client.signer_keypair = Some(Keypair::new());
+        let payer = client.signer_keypair.as_ref().unwrap().pubkey();
+        let co_signer = Keypair::new();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(payer, true), AccountMeta::new_readonly(co_signer.pubkey(), true)],
+        );
+        let transaction = CnctdSolana::compile_versioned_transaction(&payer, &[instruction], &[]).unwrap();
+
+        let missing = client.sign_versioned_transaction(&transaction, None, Hash::new_unique());
+        assert!(matches!(missing, Err(CnctdSolanaError::InvalidInput(_))));
+
+        let signed = client.sign_versioned_transaction(&transaction, Some(&[&co_signer]), Hash::new_unique()).unwrap();
+        assert_eq!(signed.signatures.len(), 2);
+    }
+}
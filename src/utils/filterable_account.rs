@@ -31,9 +31,9 @@ impl BorshSize for FixedString32 {
     }
 }
 
-impl ToString for FixedString32 {
-    fn to_string(&self) -> String {
-        self.0.clone()
+impl std::fmt::Display for FixedString32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
     }
 }
 
@@ -4,6 +4,7 @@ pub trait UuidFormatting {
     fn to_solana_seed_format(&self) -> String;
     
     /// Restores hyphens to a UUID string that has had them removed
+    #[allow(clippy::wrong_self_convention)]
     fn from_solana_seed_format(&self) -> String;
     
     /// Validates if the string is a properly formatted UUID (with or without hyphens)
@@ -54,9 +55,7 @@ impl UuidFormatting for str {
         let len = std::cmp::min(8, bytes.len());
         // Create a fixed-size array and fill it
         let mut result = [0u8; 8];
-        for i in 0..len {
-            result[i] = bytes[i];
-        }
+        result[..len].copy_from_slice(&bytes[..len]);
         result
     }
     
@@ -67,9 +66,7 @@ impl UuidFormatting for str {
         
         // Create a fixed-size array and fill it
         let mut result = [0u8; 7];
-        for i in 0..len {
-            result[i] = bytes[i];
-        }
+        result[..len].copy_from_slice(&bytes[..len]);
         
         result
     }
@@ -81,9 +78,7 @@ impl UuidFormatting for str {
         
         // Create a fixed-size array and fill it
         let mut result = [0u8; N];
-        for i in 0..len {
-            result[i] = bytes[i];
-        }
+        result[..len].copy_from_slice(&bytes[..len]);
         
         result
     }
@@ -0,0 +1,107 @@
+//! A local JSON-RPC stub for integration tests and benches, served over
+//! plain HTTP so requests go through the real `HttpTransport`.
+//!
+//! Not every target uses every accessor, hence the `dead_code` allowances.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+type Respond = dyn Fn(&str, &Value) -> Value + Send + Sync;
+
+pub struct StubRpc {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl StubRpc {
+    /// Answers each request with the `result` that `respond` returns for its
+    /// method and params
+    pub async fn start(respond: impl Fn(&str, &Value) -> Value + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind stub");
+        let url = format!("http://{}", listener.local_addr().expect("stub address"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(AtomicUsize::new(0));
+        let respond: Arc<Respond> = Arc::new(respond);
+
+        let recorded = requests.clone();
+        let opened = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                opened.fetch_add(1, Ordering::Relaxed);
+                tokio::spawn(serve(stream, respond.clone(), recorded.clone()));
+            }
+        });
+
+        Self { url, requests, connections }
+    }
+
+    /// Request bodies received so far, in order
+    #[allow(dead_code)]
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Connections opened since the last call
+    #[allow(dead_code)]
+    pub fn take_connections(&self) -> usize {
+        self.connections.swap(0, Ordering::Relaxed)
+    }
+}
+
+async fn serve(stream: TcpStream, respond: Arc<Respond>, requests: Arc<Mutex<Vec<Value>>>) -> std::io::Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut buffer = Vec::new();
+
+    loop {
+        let header_end = loop {
+            if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+                break end + 4;
+            }
+            let mut chunk = [0u8; 4096];
+            let read = reader.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        };
+
+        let headers = String::from_utf8_lossy(&buffer[..header_end]).to_ascii_lowercase();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|length| length.trim().parse().ok())
+            .unwrap_or(0);
+
+        while buffer.len() < header_end + content_length {
+            let mut chunk = [0u8; 4096];
+            let read = reader.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        }
+
+        let request: Value = serde_json::from_slice(&buffer[header_end..header_end + content_length]).unwrap_or_default();
+        buffer.drain(..header_end + content_length);
+
+        let result = respond(request["method"].as_str().unwrap_or_default(), &request["params"]);
+        let body = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+        requests.lock().unwrap().push(request);
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        writer.write_all(response.as_bytes()).await?;
+    }
+}
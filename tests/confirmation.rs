@@ -0,0 +1,119 @@
+//! `confirm_transaction` against a websocket stub for `signatureSubscribe`
+//! and `MemoryTransport` for the polled statuses and block height.
+
+use std::{sync::Arc, time::Duration};
+
+use cnctd_solana::rpc::{client::CnctdSolana, ConfirmationOutcome, MemoryTransport, RpcEndpoint, RpcMode, RpcPool};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use solana_commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::{
+    instruction::InstructionError, pubkey::Pubkey, signature::{Keypair, Signature}, signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use solana_system_interface::instruction as system_instruction;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Answers `signatureSubscribe` and then notifies `notification` as the
+/// signature's result, in slot 42
+async fn signature_stub(notification: Value) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let notification = notification.clone();
+            tokio::spawn(async move {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+                while let Some(Ok(Message::Text(text))) = ws.next().await {
+                    let request: Value = serde_json::from_str(&text).unwrap();
+                    let response = json!({ "jsonrpc": "2.0", "result": 7, "id": request["id"] });
+                    ws.send(Message::Text(response.to_string())).await.unwrap();
+
+                    if request["method"] == "signatureSubscribe" {
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": "signatureNotification",
+                            "params": {
+                                "result": { "context": { "slot": 42 }, "value": notification },
+                                "subscription": 7
+                            }
+                        });
+                        ws.send(Message::Text(notification.to_string())).await.unwrap();
+                    }
+                }
+            });
+        }
+    });
+
+    url
+}
+
+fn client(memory: &Arc<MemoryTransport>, ws_url: &str) -> CnctdSolana {
+    let endpoint = RpcEndpoint::with_transport(memory.clone()).with_ws_url(ws_url);
+    let mut client = CnctdSolana::with_pool(RpcPool::from_endpoints(vec![endpoint], RpcMode::Failover)).unwrap();
+    client.signer_keypair = Some(Keypair::new());
+    client
+}
+
+async fn confirm(client: &CnctdSolana, signature: &Signature, last_valid_block_height: u64) -> ConfirmationOutcome {
+    let confirmation = client.confirm_transaction(signature, last_valid_block_height, CommitmentConfig::confirmed());
+
+    tokio::time::timeout(Duration::from_secs(5), confirmation).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn confirms_through_the_subscription() {
+    let memory = Arc::new(MemoryTransport::new());
+    let client = client(&memory, &signature_stub(json!({ "err": null })).await);
+
+    // Never sent, so only the notification can confirm it
+    let outcome = confirm(&client, &Signature::new_unique(), 1_000).await;
+
+    assert_eq!(outcome, ConfirmationOutcome::Confirmed(CommitmentLevel::Confirmed, 42));
+}
+
+#[tokio::test]
+async fn reports_failures_notified_by_the_subscription() {
+    let memory = Arc::new(MemoryTransport::new());
+    let client = client(&memory, &signature_stub(json!({ "err": { "InstructionError": [0, { "Custom": 6000 }] } })).await);
+
+    let outcome = confirm(&client, &Signature::new_unique(), 1_000).await;
+
+    assert_eq!(outcome, ConfirmationOutcome::Failed(TransactionError::InstructionError(0, InstructionError::Custom(6000))));
+}
+
+#[tokio::test]
+async fn falls_back_to_polling_without_a_websocket() {
+    let memory = Arc::new(MemoryTransport::new());
+    // Nothing listens here, so the subscription never connects
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = client(&memory, &format!("ws://{}", listener.local_addr().unwrap()));
+    drop(listener);
+
+    let payer = client.signer_keypair.as_ref().unwrap().pubkey();
+    let transaction = Transaction::new_with_payer(&[system_instruction::transfer(&payer, &Pubkey::new_unique(), 1)], Some(&payer));
+    let (signature, last_valid_block_height) = client.sign_and_send_transaction(&transaction, None).await.unwrap();
+
+    let outcome = confirm(&client, &signature, last_valid_block_height).await;
+
+    assert_eq!(outcome, ConfirmationOutcome::Confirmed(CommitmentLevel::Finalized, memory.slot()));
+}
+
+#[tokio::test]
+async fn expires_once_the_block_height_passes() {
+    let memory = Arc::new(MemoryTransport::new());
+    let client = client(&memory, "");
+    let signature = Signature::new_unique();
+    let last_valid_block_height = 5;
+
+    let expiry = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        memory.advance_slot(last_valid_block_height);
+    };
+    let (outcome, _) = tokio::join!(confirm(&client, &signature, last_valid_block_height), expiry);
+
+    assert_eq!(outcome, ConfirmationOutcome::Expired);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use base64::Engine;
+use cnctd_solana::rpc::{client::CnctdSolana, CnctdSolanaError, RpcEndpoint, RpcMode, RpcPool};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use common::StubRpc;
+
+const OWNER: Pubkey = Pubkey::new_from_array([9; 32]);
+
+/// A URL nothing listens on, so requests to it are refused
+async fn dead_url() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+async fn live_stub() -> StubRpc {
+    StubRpc::start(|method, params| match method {
+        "getAccountInfo" if params[0] == json!(OWNER.to_string()) => json!({
+            "context": { "slot": 10 },
+            "value": {
+                "lamports": 1_000,
+                "data": [base64::engine::general_purpose::STANDARD.encode([1, 2, 3]), "base64"],
+                "owner": Pubkey::default().to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": 3,
+            }
+        }),
+        "getAccountInfo" => json!({ "context": { "slot": 10 }, "value": null }),
+        _ => Value::Null,
+    }).await
+}
+
+fn client(urls: &[&str]) -> CnctdSolana {
+    let endpoints = urls.iter().map(|url| RpcEndpoint::new(url).with_rate_limit(None)).collect();
+
+    CnctdSolana::with_pool(RpcPool::from_endpoints(endpoints, RpcMode::Failover)).unwrap()
+}
+
+#[tokio::test]
+async fn get_account_fails_over_from_a_dead_endpoint() {
+    let dead = dead_url().await;
+    let stub = live_stub().await;
+    let client = client(&[&dead, &stub.url]);
+
+    let account = client.get_account(OWNER).await.unwrap();
+    assert_eq!(account.data, vec![1, 2, 3]);
+    assert_eq!(stub.requests().len(), 1);
+
+    // A missing account is an answer, not a failure to move past
+    let missing = Pubkey::new_unique();
+    assert!(matches!(client.get_account(missing).await, Err(CnctdSolanaError::AccountNotFound(pubkey)) if pubkey == missing));
+}
+
+#[tokio::test]
+async fn get_account_reports_a_connection_error_as_retryable() {
+    let client = client(&[&dead_url().await]);
+
+    let error = client.get_account(OWNER).await.unwrap_err();
+    assert!(error.is_retryable(), "{error:?}");
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use cnctd_solana::rpc::{
+    client::CnctdSolana, CnctdSolanaError, PriorityFeeOptions, PriorityLevel, RpcCapability, RpcEndpoint, RpcMode, RpcPool,
+    SearchAssets,
+};
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use solana_system_interface::instruction as system_instruction;
+
+use common::StubRpc;
+
+fn asset(id: &str, owner: &str) -> Value {
+    json!({
+        "id": id,
+        "interface": "V1_NFT",
+        "content": {
+            "json_uri": "https://example.com/asset.json",
+            "metadata": { "name": "Asset", "symbol": "AST" }
+        },
+        "ownership": { "owner": owner, "delegated": false, "frozen": false, "ownership_model": "single" },
+        "mutable": true,
+        "burnt": false,
+        "royalty": { "basis_points": 500 }
+    })
+}
+
+/// A Helius-like stub answering the fee estimate and DAS methods
+async fn helius_stub() -> StubRpc {
+    StubRpc::start(|method, params| match method {
+        "getPriorityFeeEstimate" => json!({
+            "priorityFeeEstimate": 12_000.0,
+            "priorityFeeLevels": {
+                "min": 0.0, "low": 100.0, "medium": 1_000.0, "high": 12_000.0, "veryHigh": 50_000.0, "unsafeMax": 900_000.0
+            }
+        }),
+        "getAsset" => asset(params["id"].as_str().unwrap(), "11111111111111111111111111111111"),
+        "getAssetsByOwner" | "searchAssets" => {
+            let owner = params["ownerAddress"].as_str().unwrap();
+            json!({ "total": 1, "limit": params["limit"], "page": params["page"], "items": [asset("Asset1111111111111111111111111111111111111", owner)] })
+        }
+        _ => Value::Null,
+    }).await
+}
+
+fn client(url: &str, capabilities: Vec<RpcCapability>) -> CnctdSolana {
+    let endpoint = RpcEndpoint::new(url).with_rate_limit(None).with_capabilities(capabilities);
+
+    CnctdSolana::with_pool(RpcPool::from_endpoints(vec![endpoint], RpcMode::Failover)).unwrap()
+}
+
+#[tokio::test]
+async fn get_priority_fee_estimate_sends_the_transaction_and_options() {
+    let stub = helius_stub().await;
+    let client = client(&stub.url, vec![RpcCapability::PriorityFeeEstimate]);
+    let payer = Pubkey::new_unique();
+    let transaction = Transaction::new_with_payer(&[system_instruction::transfer(&payer, &Pubkey::new_unique(), 1)], Some(&payer));
+
+    let options = PriorityFeeOptions { include_all_priority_fee_levels: Some(true), ..PriorityFeeOptions::default() };
+    let estimate = client.get_priority_fee_estimate(&transaction, options).await.unwrap();
+
+    assert_eq!(estimate.priority_fee_estimate, Some(12_000.0));
+    assert_eq!(estimate.priority_fee_levels.unwrap().very_high, 50_000.0);
+
+    let request = &stub.requests()[0];
+    assert_eq!(request["method"], "getPriorityFeeEstimate");
+    assert!(request["params"][0]["transaction"].is_string());
+    assert_eq!(request["params"][0]["options"], json!({ "includeAllPriorityFeeLevels": true, "transactionEncoding": "Base64" }));
+}
+
+#[tokio::test]
+async fn get_priority_fee_estimate_for_accounts_sends_the_keys() {
+    let stub = helius_stub().await;
+    let client = client(&stub.url, vec![RpcCapability::PriorityFeeEstimate]);
+    let account = Pubkey::new_unique();
+
+    let options = PriorityFeeOptions { priority_level: Some(PriorityLevel::High), ..PriorityFeeOptions::default() };
+    client.get_priority_fee_estimate_for_accounts(&[account], options).await.unwrap();
+
+    let request = &stub.requests()[0];
+    assert_eq!(request["params"], json!([{ "accountKeys": [account.to_string()], "options": { "priorityLevel": "High" } }]));
+}
+
+#[tokio::test]
+async fn das_methods_send_named_params() {
+    let stub = helius_stub().await;
+    let client = client(&stub.url, vec![RpcCapability::DigitalAssetStandard]);
+    let id = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    let asset = client.get_asset(&id).await.unwrap();
+    assert_eq!(asset.id, id.to_string());
+    assert_eq!(asset.content.unwrap().metadata.symbol, "AST");
+    assert_eq!(asset.other["royalty"], json!({ "basis_points": 500 }));
+
+    let assets = client.get_assets_by_owner(&owner, 2, 10).await.unwrap();
+    assert_eq!((assets.total, assets.limit, assets.page), (1, 10, Some(2)));
+    assert_eq!(assets.items[0].ownership.as_ref().unwrap().owner, owner.to_string());
+
+    let search = SearchAssets { owner_address: Some(owner.to_string()), compressed: Some(true), page: Some(1), limit: Some(5), ..SearchAssets::default() };
+    let assets = client.search_assets(&search).await.unwrap();
+    assert_eq!(assets.limit, 5);
+
+    // DAS takes an object of params, not the positional array RpcClient sends
+    let requests = stub.requests();
+    assert_eq!(requests[0]["params"], json!({ "id": id.to_string() }));
+    assert_eq!(requests[1]["params"], json!({ "ownerAddress": owner.to_string(), "page": 2, "limit": 10 }));
+    assert_eq!(requests[2]["method"], "searchAssets");
+    assert_eq!(requests[2]["params"], json!({ "ownerAddress": owner.to_string(), "compressed": true, "page": 1, "limit": 5 }));
+}
+
+#[tokio::test]
+async fn provider_methods_need_an_endpoint_with_the_capability() {
+    let stub = helius_stub().await;
+    let client = client(&stub.url, vec![RpcCapability::PriorityFeeEstimate]);
+
+    assert!(client.supports(RpcCapability::PriorityFeeEstimate));
+    assert!(!client.supports(RpcCapability::DigitalAssetStandard));
+    assert!(matches!(client.get_asset(&Pubkey::new_unique()).await, Err(CnctdSolanaError::Config(_))));
+    assert!(stub.requests().is_empty());
+}
@@ -0,0 +1,240 @@
+//! Replays `tests/fixtures/replay.json`, recorded from the stub in
+//! `record_fixture`, so these tests run with no network access.
+
+mod common;
+
+use std::str::FromStr;
+
+use base64::Engine;
+use borsh::{BorshDeserialize, BorshSerialize};
+use cnctd_solana::{
+    rpc::{
+        client::CnctdSolana,
+        fixture::{RecordedResponse, RpcExchange},
+        PriorityFee, ReplaySender, RpcEndpoint, RpcFixture, RpcMode, RpcPool,
+    },
+    utils::FilterableAccount,
+};
+use serde_json::{json, Value};
+use solana_client::rpc_request::RpcRequest;
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::rpc_sender::RpcSender;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+use solana_system_interface::instruction as system_instruction;
+
+use common::StubRpc;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/replay.json");
+
+const LISTING_DISCRIMINATOR: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([1; 32]);
+const SELLER: Pubkey = Pubkey::new_from_array([2; 32]);
+const RECIPIENT: Pubkey = Pubkey::new_from_array([3; 32]);
+
+/// Lamports the simulated transfer leaves in the new account
+const RECIPIENT_LAMPORTS: u64 = 890_880;
+
+#[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+struct Listing {
+    seller: Pubkey,
+    price: u64,
+}
+
+impl FilterableAccount for Listing {
+    fn discriminator() -> [u8; 8] {
+        LISTING_DISCRIMINATOR
+    }
+
+    fn get_field_offset(field_name: &str) -> Option<usize> {
+        match field_name {
+            "seller" => Some(8),
+            "price" => Some(40),
+            _ => None,
+        }
+    }
+
+    fn serialize_field_value(field_name: &str, value: &Value) -> Option<Vec<u8>> {
+        match field_name {
+            "seller" => Pubkey::from_str(value.as_str()?).ok().map(|pubkey| pubkey.to_bytes().to_vec()),
+            "price" => value.as_u64().map(|price| price.to_le_bytes().to_vec()),
+            _ => None,
+        }
+    }
+}
+
+fn payer() -> Keypair {
+    Keypair::new_from_array([7; 32])
+}
+
+fn transfer() -> Transaction {
+    let payer = payer().pubkey();
+
+    Transaction::new_with_payer(&[system_instruction::transfer(&payer, &RECIPIENT, 1_000_000)], Some(&payer))
+}
+
+fn ui_account(lamports: u64, owner: &Pubkey, data: &[u8]) -> Value {
+    json!({
+        "lamports": lamports,
+        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        "owner": owner.to_string(),
+        "executable": false,
+        "rentEpoch": 0,
+        "space": data.len(),
+    })
+}
+
+/// What the node answered while recording
+fn respond(method: &str, params: &Value) -> Value {
+    match method {
+        "getProgramAccounts" => {
+            let mut data = LISTING_DISCRIMINATOR.to_vec();
+            data.extend(borsh::to_vec(&Listing { seller: SELLER, price: 25 }).unwrap());
+
+            json!([{ "pubkey": Pubkey::new_from_array([4; 32]).to_string(), "account": ui_account(1_000_000, &PROGRAM_ID, &data) }])
+        }
+        "getLatestBlockhash" => json!({
+            "context": { "slot": 100 },
+            "value": { "blockhash": Hash::new_from_array([5; 32]).to_string(), "lastValidBlockHeight": 250 }
+        }),
+        "getRecentPrioritizationFees" => json!([100, 300, 500, 700]
+            .iter()
+            .enumerate()
+            .map(|(slot, fee)| json!({ "slot": slot, "prioritizationFee": fee }))
+            .collect::<Vec<_>>()),
+        "getMultipleAccounts" => json!({
+            "context": { "slot": 101 },
+            "value": [ui_account(5_000_000_000, &Pubkey::default(), &[]), null]
+        }),
+        "simulateTransaction" if params[1]["accounts"].is_object() => json!({
+            "context": { "slot": 102 },
+            "value": {
+                "err": null,
+                "logs": [],
+                "accounts": [ui_account(4_998_995_000, &Pubkey::default(), &[]), ui_account(RECIPIENT_LAMPORTS, &Pubkey::default(), &[])],
+                "unitsConsumed": 150
+            }
+        }),
+        "simulateTransaction" => json!({
+            "context": { "slot": 102 },
+            "value": {
+                "err": null,
+                "logs": ["Program 11111111111111111111111111111111 invoke [1]", "Program 11111111111111111111111111111111 success"],
+                "unitsConsumed": 150
+            }
+        }),
+        _ => Value::Null,
+    }
+}
+
+/// Each call gets a client of its own, so its params do not depend on the
+/// slots earlier calls observed
+fn client(pool: &RpcPool) -> CnctdSolana {
+    let mut client = CnctdSolana::with_pool(pool.clone()).unwrap();
+    client.signer_keypair = Some(payer());
+    client
+}
+
+fn replay_pool() -> RpcPool {
+    RpcPool::from_endpoints(vec![RpcEndpoint::replaying(FIXTURE).unwrap()], RpcMode::Failover)
+}
+
+/// Rewrites the fixture from the stub: `cargo test --test replay -- --ignored`
+#[tokio::test]
+#[ignore]
+async fn record_fixture() {
+    let stub = StubRpc::start(respond).await;
+    let endpoint = RpcEndpoint::recording(&stub.url, FIXTURE).with_rate_limit(None);
+    let pool = RpcPool::from_endpoints(vec![endpoint], RpcMode::Failover);
+
+    client(&pool).get_accounts_by_fields::<Listing>(PROGRAM_ID, &[("seller", json!(SELLER.to_string()))]).await.unwrap();
+    client(&pool).simulate_transaction(&transfer(), None).await.unwrap();
+    client(&pool).estimate_transaction_fee(&transfer(), PriorityFee::Medium).await.unwrap();
+
+    assert_eq!(RpcFixture::from_file(FIXTURE).unwrap().exchanges.len(), stub.requests().len());
+}
+
+#[tokio::test]
+async fn replays_get_accounts_by_fields() {
+    let listings = client(&replay_pool())
+        .get_accounts_by_fields::<Listing>(PROGRAM_ID, &[("seller", json!(SELLER.to_string()))])
+        .await
+        .unwrap();
+
+    assert_eq!(listings, vec![(Pubkey::new_from_array([4; 32]), Listing { seller: SELLER, price: 25 })]);
+
+    // A different filter was never recorded
+    let unrecorded = client(&replay_pool())
+        .get_accounts_by_fields::<Listing>(PROGRAM_ID, &[("price", json!(25))])
+        .await;
+    assert!(unrecorded.is_err());
+}
+
+#[tokio::test]
+async fn replays_simulate_transaction() {
+    let simulation = client(&replay_pool()).simulate_transaction(&transfer(), None).await.unwrap();
+
+    assert!(simulation.err.is_none());
+    assert_eq!(simulation.units_consumed, Some(150));
+    assert_eq!(simulation.logs.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn replays_estimate_transaction_fee() {
+    let estimate = client(&replay_pool()).estimate_transaction_fee(&transfer(), PriorityFee::Medium).await.unwrap();
+
+    assert_eq!(estimate.tiers.medium, 300);
+    assert_eq!(estimate.compute_unit_price, 300);
+    // One instruction at the default 200,000 units, at 300 micro-lamports each
+    assert_eq!(estimate.priority_fee, 60);
+    assert_eq!(estimate.compute_units_consumed, 150);
+    assert_eq!(estimate.rent, RECIPIENT_LAMPORTS);
+    assert_eq!(estimate.total(), 5_000 + 60 + RECIPIENT_LAMPORTS);
+}
+
+/// Commitments sent with each `simulateTransaction` request
+fn simulation_commitments(stub: &StubRpc) -> Vec<Value> {
+    stub.requests()
+        .iter()
+        .filter(|request| request["method"] == "simulateTransaction")
+        .map(|request| request["params"][1]["commitment"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn simulates_at_confirmed_unless_told_otherwise() {
+    let stub = StubRpc::start(respond).await;
+    let pool = RpcPool::from_endpoints(vec![RpcEndpoint::new(&stub.url).with_rate_limit(None)], RpcMode::Failover);
+    // Instances default to finalized, which may not know a fresh blockhash
+    assert_eq!(client(&pool).commitment, CommitmentConfig::finalized());
+
+    client(&pool).simulate_transaction(&transfer(), None).await.unwrap();
+    client(&pool).estimate_transaction_fee(&transfer(), PriorityFee::Medium).await.unwrap();
+    assert_eq!(simulation_commitments(&stub), vec![json!("confirmed"); 2]);
+
+    client(&pool)
+        .estimate_transaction_fee_with_commitment(&transfer(), PriorityFee::Medium, CommitmentConfig::finalized())
+        .await
+        .unwrap();
+    assert_eq!(simulation_commitments(&stub)[2], json!("finalized"));
+}
+
+#[tokio::test]
+async fn repeats_the_last_response_once_they_run_out() {
+    let exchange = |slot: u64| RpcExchange {
+        method: "getSlot".to_string(),
+        params: json!([]),
+        response: RecordedResponse::Result(json!(slot)),
+    };
+    let sender = ReplaySender::new(RpcFixture {
+        url: "http://127.0.0.1:8899".to_string(),
+        exchanges: vec![exchange(10), exchange(11)],
+    });
+
+    let mut slots = Vec::new();
+    for _ in 0..3 {
+        slots.push(sender.send(RpcRequest::GetSlot, json!([])).await.unwrap());
+    }
+
+    assert_eq!(slots, vec![json!(10), json!(11), json!(11)]);
+}